@@ -21,12 +21,53 @@ const tri = wasm.Triangle.new(
 //   -1.0 // gravity_y
 // );
 
-const mpm = wasm.RustMlsMpm.new(document.getElementById("canvas2"), 500, 40);
+const mpmParams = wasm.SimParams.new(0.0, -200.0, 0.05);
+mpmParams.time_scale = 0.02;
+const mpm = wasm.RustMlsMpm.new(
+  document.getElementById("canvas2"),
+  500,
+  40,
+  undefined,
+  mpmParams
+);
+const snow = mpm.add_material(
+  wasm.MaterialParams.new(1.0, 1.0, 10.0, 10000.0, 0.2, wasm.PlasticityModel.Snow)
+);
+mpm.add_particles(500, 0.25, 0.25, 0xffffffff, snow);
+const water = mpm.add_material(wasm.MaterialParams.water());
+mpm.add_particles(500, 0.75, 0.2, 0xffff8000, water);
+
+// Drag to grab the material, shift-drag to pull it in, right-drag to push it away
+const mpmCanvas = document.getElementById("canvas2");
+const pointerMode = (event) => {
+  if (event.buttons & 2) return wasm.PointerMode.Push;
+  if (event.shiftKey) return wasm.PointerMode.Pull;
+  return wasm.PointerMode.Grab;
+};
+const setPointer = (event) => {
+  if (event.buttons === 0) return;
+  mpm.set_pointer(event.offsetX, event.offsetY, 0.08, pointerMode(event), 2000.0);
+};
+mpmCanvas.style.touchAction = "none";
+mpmCanvas.addEventListener("contextmenu", (event) => event.preventDefault());
+mpmCanvas.addEventListener("pointerdown", (event) => {
+  mpmCanvas.setPointerCapture(event.pointerId);
+  setPointer(event);
+});
+mpmCanvas.addEventListener("pointermove", setPointer);
+mpmCanvas.addEventListener("pointerup", () => mpm.clear_pointer());
+mpmCanvas.addEventListener("pointercancel", () => mpm.clear_pointer());
+
+let lastFrameTime = performance.now();
 
 const renderLoop = () => {
+  const frameTime = performance.now();
+  const realDt = (frameTime - lastFrameTime) / 1000;
+  lastFrameTime = frameTime;
+
   tri.draw();
   //sp.draw(0.01);
-  mpm.draw(1e-4);
+  mpm.draw(realDt);
   animationId = requestAnimationFrame(renderLoop);
   numFrames += 1;
 
@@ -66,6 +66,10 @@ impl Context {
         );
     }
 
+    pub fn set_uniform_vec2(&self, location: &WebGlUniformLocation, x: f32, y: f32) {
+        self.0.uniform2f(Some(location), x, y);
+    }
+
     // pub fn bind_buffer(&self, target: u32, buffer: &Buffer) {
     //     self.0.bind_buffer(target, Some(&buffer.0))
     // }
@@ -208,7 +212,7 @@ impl Program {
             .ok_or_else(|| String::from("Unable to create shader object"))?;
 
         shaders
-            .into_iter()
+            .iter()
             .for_each(|shader| ctx.0.attach_shader(&program, &shader.0));
 
         if let Some(varyings) = transform_feedback_varyings {
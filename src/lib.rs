@@ -1,5 +1,7 @@
 mod gl;
 mod linalg;
+pub mod mpm;
+mod random;
 pub mod simulations;
 
 use wasm_bindgen::prelude::*;
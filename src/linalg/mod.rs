@@ -22,9 +22,9 @@ pub fn polar_decomp(m: glam::Mat2) -> (glam::Mat2, glam::Mat2) {
     let scale = 1.0 / (x * x + y * y).sqrt();
     let c = x * scale;
     let s = y * scale;
-    let R = glam::Mat2::from_cols(glam::Vec2::new(c, s), glam::Vec2::new(-s, c));
-    let S = R.transpose() * m;
-    (R, S)
+    let rot = glam::Mat2::from_cols(glam::Vec2::new(c, s), glam::Vec2::new(-s, c));
+    let sym = rot.transpose() * m;
+    (rot, sym)
 }
 
 #[inline(always)]
@@ -39,21 +39,21 @@ pub fn svd(m: glam::Mat2) -> (glam::Mat2, glam::Mat2, glam::Mat2) {
 }
 
 pub fn svd_inner(m: glam::Mat2, u: &mut glam::Mat2, sig: &mut glam::Mat2, v: &mut glam::Mat2) {
-    let (u_, S) = polar_decomp(m);
+    let (u_, sym) = polar_decomp(m);
     *u = u_;
 
-    let mut c = 0.0;
-    let mut s = 0.0;
+    let c;
+    let s;
 
-    // TODO: Might need to make this S.row(1).x
-    let some_value = S.row(0).y;
+    // TODO: Might need to make this sym.row(1).x
+    let some_value = sym.row(0).y;
 
     if some_value.abs() < 1e-6 {
-        *sig = S;
+        *sig = sym;
         c = 1.0;
         s = 0.0;
     } else {
-        let tao = 0.5 * (S.row(0).x - S.row(1).y);
+        let tao = 0.5 * (sym.row(0).x - sym.row(1).y);
         let w = (tao * tao + some_value * some_value).sqrt();
         let t = if tao > 0.0 {
             some_value / (tao + w)
@@ -62,8 +62,8 @@ pub fn svd_inner(m: glam::Mat2, u: &mut glam::Mat2, sig: &mut glam::Mat2, v: &mu
         };
         c = 1.0 / (t * t + 1.0).sqrt();
         s = -t * c;
-        sig.col_mut(0).x = c * c * S.col(0).x - 2.0 * c * s * some_value + s * s * S.col(1).y;
-        sig.col_mut(1).y = s * s * S.col(0).x + 2.0 * c * s * some_value + c * c * S.col(1).y;
+        sig.col_mut(0).x = c * c * sym.col(0).x - 2.0 * c * s * some_value + s * s * sym.col(1).y;
+        sig.col_mut(1).y = s * s * sym.col(0).x + 2.0 * c * s * some_value + c * c * sym.col(1).y;
     }
 
     if sig.col(0).x < sig.col(1).y {
@@ -76,5 +76,5 @@ pub fn svd_inner(m: glam::Mat2, u: &mut glam::Mat2, sig: &mut glam::Mat2, v: &mu
     }
 
     *v = v.transpose();
-    *u = *u * *v;
+    *u *= *v;
 }
@@ -0,0 +1,101 @@
+use wasm_bindgen::prelude::*;
+
+use super::Vec2;
+
+/// One side of the domain.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Wall {
+    Left,
+    Right,
+    Bottom,
+    Top,
+}
+
+impl Wall {
+    pub const ALL: [Wall; 4] = [Wall::Left, Wall::Right, Wall::Bottom, Wall::Top];
+
+    /// Unit normal pointing into the domain
+    pub fn normal(self) -> Vec2 {
+        match self {
+            Wall::Left => Vec2::X,
+            Wall::Right => -Vec2::X,
+            Wall::Bottom => Vec2::Y,
+            Wall::Top => -Vec2::Y,
+        }
+    }
+}
+
+/// How grid velocities are constrained near a wall.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum BoundaryType {
+    /// No motion at all
+    Sticky,
+    /// No motion through the wall, free to slide along it
+    Slip,
+    /// Like slip, but material may move away from the wall
+    Separate,
+    /// No wall: material that leaves the domain is removed
+    Open,
+}
+
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, serde::Serialize, serde::Deserialize)]
+pub struct Boundary {
+    kind: BoundaryType,
+    friction: f32,
+}
+
+#[wasm_bindgen]
+impl Boundary {
+    pub fn new(kind: BoundaryType, friction: f32) -> Self {
+        Self { kind, friction }
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn kind(&self) -> BoundaryType {
+        self.kind
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_kind(&mut self, kind: BoundaryType) {
+        self.kind = kind;
+    }
+
+    /// Coulomb friction coefficient for slip and separating walls
+    #[wasm_bindgen(getter)]
+    pub fn friction(&self) -> f32 {
+        self.friction
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_friction(&mut self, friction: f32) {
+        self.friction = friction;
+    }
+}
+
+impl Boundary {
+    /// Applies this wall's condition to a grid velocity, given the wall's
+    /// inward normal.
+    pub fn project(&self, velocity: Vec2, normal: Vec2) -> Vec2 {
+        let normal_speed = velocity.dot(normal);
+
+        match self.kind {
+            BoundaryType::Sticky => Vec2::ZERO,
+            BoundaryType::Open => velocity,
+            BoundaryType::Separate if normal_speed >= 0.0 => velocity,
+            BoundaryType::Slip | BoundaryType::Separate => {
+                let tangent = velocity - normal_speed * normal;
+                let tangent_speed = tangent.length();
+                if tangent_speed <= 0.0 {
+                    return tangent;
+                }
+
+                // Coulomb friction removes up to `friction * |v_n|` of sliding
+                let friction = self.friction * normal_speed.abs();
+                tangent * ((tangent_speed - friction).max(0.0) / tangent_speed)
+            }
+        }
+    }
+}
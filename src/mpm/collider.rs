@@ -0,0 +1,314 @@
+use wasm_bindgen::prelude::*;
+
+use super::{Boundary, Mat2, Vec2};
+
+/// Shapes in the collider's own frame, centred on its position
+#[derive(Clone, Copy, Debug, serde::Serialize, serde::Deserialize)]
+enum Shape {
+    Circle {
+        radius: f32,
+    },
+    Box {
+        half_extents: Vec2,
+    },
+    /// Everything behind the plane is solid
+    Plane {
+        normal: Vec2,
+    },
+    /// Segment from `-half_length` to `half_length`
+    Capsule {
+        half_length: Vec2,
+        radius: f32,
+    },
+}
+
+/// Obstacle described by an analytic signed distance field. Grid nodes
+/// inside it have the collider's boundary condition applied, using the
+/// surface normal in place of a wall normal. `BoundaryType::Open` colliders
+/// have no effect.
+///
+/// Colliders are kinematic: they move with their velocity every step, and
+/// constrain the material relative to their own motion, so setting a pose
+/// and velocity each frame is enough to drive paddles and pistons.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, serde::Serialize, serde::Deserialize)]
+pub struct Collider {
+    shape: Shape,
+    boundary: Boundary,
+    position: Vec2,
+    /// Anticlockwise, in radians
+    rotation: f32,
+    velocity: Vec2,
+    angular_velocity: f32,
+}
+
+#[wasm_bindgen]
+impl Collider {
+    pub fn circle(center_x: f32, center_y: f32, radius: f32, boundary: Boundary) -> Self {
+        Self::at(
+            Vec2::new(center_x, center_y),
+            Shape::Circle { radius },
+            boundary,
+        )
+    }
+
+    /// Axis-aligned box, given by its center and half its width and height
+    pub fn rectangle(
+        center_x: f32,
+        center_y: f32,
+        half_width: f32,
+        half_height: f32,
+        boundary: Boundary,
+    ) -> Self {
+        Self::at(
+            Vec2::new(center_x, center_y),
+            Shape::Box {
+                half_extents: Vec2::new(half_width, half_height),
+            },
+            boundary,
+        )
+    }
+
+    /// Half-space through a point, solid on the side opposite the normal
+    pub fn plane(
+        point_x: f32,
+        point_y: f32,
+        normal_x: f32,
+        normal_y: f32,
+        boundary: Boundary,
+    ) -> Self {
+        Self::at(
+            Vec2::new(point_x, point_y),
+            Shape::Plane {
+                normal: Vec2::new(normal_x, normal_y).normalize_or_zero(),
+            },
+            boundary,
+        )
+    }
+
+    /// Segment from a to b, thickened by `radius`
+    pub fn capsule(ax: f32, ay: f32, bx: f32, by: f32, radius: f32, boundary: Boundary) -> Self {
+        let a = Vec2::new(ax, ay);
+        let b = Vec2::new(bx, by);
+        Self::at(
+            0.5 * (a + b),
+            Shape::Capsule {
+                half_length: 0.5 * (b - a),
+                radius,
+            },
+            boundary,
+        )
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn boundary(&self) -> Boundary {
+        self.boundary
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_boundary(&mut self, boundary: Boundary) {
+        self.boundary = boundary;
+    }
+
+    /// Center of the shape, or the point the plane passes through
+    #[wasm_bindgen(getter)]
+    pub fn position_x(&self) -> f32 {
+        self.position.x
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn position_y(&self) -> f32 {
+        self.position.y
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn rotation(&self) -> f32 {
+        self.rotation
+    }
+
+    /// Moves the collider, rotating it by `rotation` radians from the pose
+    /// it was created in.
+    pub fn set_pose(&mut self, x: f32, y: f32, rotation: f32) {
+        self.position = Vec2::new(x, y);
+        self.rotation = rotation;
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn velocity_x(&self) -> f32 {
+        self.velocity.x
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn velocity_y(&self) -> f32 {
+        self.velocity.y
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn angular_velocity(&self) -> f32 {
+        self.angular_velocity
+    }
+
+    /// Sets the motion the collider keeps until changed, in world units and
+    /// radians per second.
+    pub fn set_velocity(&mut self, velocity_x: f32, velocity_y: f32, angular_velocity: f32) {
+        self.velocity = Vec2::new(velocity_x, velocity_y);
+        self.angular_velocity = angular_velocity;
+    }
+}
+
+impl Collider {
+    fn at(position: Vec2, shape: Shape, boundary: Boundary) -> Self {
+        Self {
+            shape,
+            boundary,
+            position,
+            rotation: 0.0,
+            velocity: Vec2::ZERO,
+            angular_velocity: 0.0,
+        }
+    }
+
+    /// Moves the collider along its velocity.
+    pub fn advance(&mut self, dt: f32) {
+        self.position += dt * self.velocity;
+        self.rotation += dt * self.angular_velocity;
+    }
+
+    pub fn position(&self) -> Vec2 {
+        self.position
+    }
+
+    pub fn translate(&mut self, offset: Vec2) {
+        self.position += offset;
+    }
+
+    pub fn velocity(&self) -> Vec2 {
+        self.velocity
+    }
+
+    /// Mass and moment of inertia about the position, for a uniform density
+    /// per unit area. Planes are unbounded, so theirs are infinite. Capsule
+    /// inertia treats the end caps as one disc at each end, which is close
+    /// enough for dynamics.
+    pub fn mass_properties(&self, density: f32) -> (f32, f32) {
+        use std::f32::consts::PI;
+
+        match self.shape {
+            Shape::Circle { radius } => {
+                let mass = density * PI * radius * radius;
+                (mass, 0.5 * mass * radius * radius)
+            }
+            Shape::Box { half_extents } => {
+                let mass = density * 4.0 * half_extents.x * half_extents.y;
+                (mass, mass * half_extents.length_squared() / 3.0)
+            }
+            Shape::Plane { .. } => (f32::INFINITY, f32::INFINITY),
+            Shape::Capsule {
+                half_length,
+                radius,
+            } => {
+                let length = 2.0 * half_length.length();
+                let body = density * length * 2.0 * radius;
+                let caps = density * PI * radius * radius;
+                let inertia = body * (length * length + 4.0 * radius * radius) / 12.0
+                    + caps * (0.5 * radius * radius + 0.25 * length * length);
+                (body + caps, inertia)
+            }
+        }
+    }
+
+    /// The point of the shape furthest along a unit `direction`. Planes
+    /// have none, so this is their position, or infinitely far away if the
+    /// direction points into them.
+    pub fn support_point(&self, direction: Vec2) -> Vec2 {
+        let rotation = Mat2::from_angle(self.rotation);
+        let local = rotation.transpose() * direction;
+
+        let offset = match self.shape {
+            Shape::Circle { radius } => radius * local,
+            Shape::Box { half_extents } => local.signum() * half_extents,
+            Shape::Plane { normal } => {
+                if local.dot(normal) < 0.0 {
+                    Vec2::ZERO
+                } else {
+                    Vec2::splat(f32::INFINITY)
+                }
+            }
+            Shape::Capsule {
+                half_length,
+                radius,
+            } => half_length * local.dot(half_length).signum() + radius * local,
+        };
+
+        self.position + rotation * offset
+    }
+
+    /// Velocity of the collider's material at a world position.
+    pub fn velocity_at(&self, position: Vec2) -> Vec2 {
+        self.velocity + self.angular_velocity * (position - self.position).perp()
+    }
+
+    /// Signed distance from `position` to the surface, negative inside, and
+    /// the outward surface normal there.
+    pub fn signed_distance(&self, position: Vec2) -> (f32, Vec2) {
+        let rotation = Mat2::from_angle(self.rotation);
+        let local = rotation.transpose() * (position - self.position);
+
+        let (distance, normal) = self.local_signed_distance(local);
+        (distance, rotation * normal)
+    }
+
+    fn local_signed_distance(&self, offset: Vec2) -> (f32, Vec2) {
+        match self.shape {
+            Shape::Circle { radius } => (offset.length() - radius, direction_or_up(offset)),
+            Shape::Box { half_extents } => {
+                let q = offset.abs() - half_extents;
+                let sign = offset.signum();
+
+                if q.x > 0.0 || q.y > 0.0 {
+                    let outside = q.max(Vec2::ZERO);
+                    (outside.length(), direction_or_up(outside * sign))
+                } else if q.x > q.y {
+                    // Inside, nearest to a vertical side
+                    (q.x, Vec2::new(sign.x, 0.0))
+                } else {
+                    (q.y, Vec2::new(0.0, sign.y))
+                }
+            }
+            Shape::Plane { normal } => (offset.dot(normal), normal),
+            Shape::Capsule {
+                half_length,
+                radius,
+            } => {
+                let t = if half_length.length_squared() > 0.0 {
+                    (offset.dot(half_length) / half_length.length_squared()).clamp(-1.0, 1.0)
+                } else {
+                    0.0
+                };
+                let offset = offset - t * half_length;
+                (offset.length() - radius, direction_or_up(offset))
+            }
+        }
+    }
+
+    /// Applies the collider to a grid velocity at `position`. The boundary
+    /// condition acts on the velocity relative to the collider's surface.
+    pub fn project(&self, position: Vec2, velocity: Vec2) -> Vec2 {
+        let (distance, normal) = self.signed_distance(position);
+        if distance > 0.0 {
+            return velocity;
+        }
+
+        let surface_velocity = self.velocity_at(position);
+        surface_velocity + self.boundary.project(velocity - surface_velocity, normal)
+    }
+}
+
+fn direction_or_up(v: Vec2) -> Vec2 {
+    let n = v.normalize_or_zero();
+    if n == Vec2::ZERO {
+        Vec2::Y
+    } else {
+        n
+    }
+}
@@ -0,0 +1,126 @@
+use glam::IVec2;
+use wasm_bindgen::prelude::*;
+
+use super::Vec2;
+
+/// The rectangle of world space the background grid covers.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, serde::Serialize, serde::Deserialize)]
+pub struct Domain {
+    origin: Vec2,
+    cell_size: f32,
+    grid_x: usize,
+    grid_y: usize,
+}
+
+#[wasm_bindgen]
+impl Domain {
+    /// A domain of `grid_x` by `grid_y` cells of `cell_size` world units,
+    /// with its bottom-left corner at the origin given. Fails unless there
+    /// is at least one cell of positive, finite size.
+    pub fn new(
+        origin_x: f32,
+        origin_y: f32,
+        cell_size: f32,
+        grid_x: usize,
+        grid_y: usize,
+    ) -> Result<Domain, String> {
+        let domain = Self {
+            origin: Vec2::new(origin_x, origin_y),
+            cell_size,
+            grid_x,
+            grid_y,
+        };
+        domain.validate()?;
+        Ok(domain)
+    }
+
+    /// The unit square, split into `grid_size` cells per side. Panics if
+    /// `grid_size` is zero.
+    pub fn unit_square(grid_size: usize) -> Self {
+        Self::new(0.0, 0.0, 1.0 / grid_size as f32, grid_size, grid_size)
+            .expect("Unit square needs at least one cell")
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn origin_x(&self) -> f32 {
+        self.origin.x
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn origin_y(&self) -> f32 {
+        self.origin.y
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn cell_size(&self) -> f32 {
+        self.cell_size
+    }
+
+    /// Number of cells along x
+    #[wasm_bindgen(getter)]
+    pub fn grid_x(&self) -> usize {
+        self.grid_x
+    }
+
+    /// Number of cells along y
+    #[wasm_bindgen(getter)]
+    pub fn grid_y(&self) -> usize {
+        self.grid_y
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn width(&self) -> f32 {
+        self.grid_x as f32 * self.cell_size
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn height(&self) -> f32 {
+        self.grid_y as f32 * self.cell_size
+    }
+}
+
+impl Domain {
+    /// Checks the domain has cells to divide by and index into, as
+    /// deserialized domains skip `new`.
+    pub(crate) fn validate(&self) -> Result<(), String> {
+        if !self.origin.is_finite() {
+            return Err("Domain origin must be finite".into());
+        }
+        if !(self.cell_size > 0.0 && self.cell_size.is_finite()) {
+            return Err("Domain cell size must be positive and finite".into());
+        }
+        if self.grid_x == 0 || self.grid_y == 0 {
+            return Err("Domain must have at least one cell along each axis".into());
+        }
+        Ok(())
+    }
+
+    /// Bottom-left corner
+    pub fn origin(&self) -> Vec2 {
+        self.origin
+    }
+
+    pub fn size(&self) -> Vec2 {
+        Vec2::new(self.width(), self.height())
+    }
+
+    pub fn center(&self) -> Vec2 {
+        self.origin + 0.5 * self.size()
+    }
+
+    /// Converts a world position to grid cells.
+    pub fn to_grid(&self, position: Vec2) -> Vec2 {
+        (position - self.origin) / self.cell_size
+    }
+
+    /// World position of a grid node.
+    pub fn node_position(&self, coord: IVec2) -> Vec2 {
+        self.origin + coord.as_vec2() * self.cell_size
+    }
+
+    pub fn contains(&self, position: Vec2) -> bool {
+        let local = position - self.origin;
+        local.x >= 0.0 && local.y >= 0.0 && local.x <= self.width() && local.y <= self.height()
+    }
+}
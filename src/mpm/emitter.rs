@@ -0,0 +1,194 @@
+use wasm_bindgen::prelude::*;
+
+use super::{Particle, Vec2};
+
+/// Fractional part of the golden ratio, which spreads successive particles
+/// evenly across the nozzle without repeating a pattern
+const GOLDEN_FRACTION: f32 = 0.618_034;
+
+/// Most particles one emitter adds in a single step. Any more owed, say after
+/// a long step, is dropped rather than flooding the scene
+const MAX_PER_STEP: f32 = 1000.0;
+
+/// Negative or non-finite rates stop the emitter
+fn valid_rate(rate: f32) -> f32 {
+    if rate.is_finite() {
+        rate.max(0.0)
+    } else {
+        0.0
+    }
+}
+
+/// A nozzle that adds particles at a steady rate, like a faucet.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, serde::Serialize, serde::Deserialize)]
+pub struct Emitter {
+    position: Vec2,
+    /// Unit vector particles leave along
+    direction: Vec2,
+    speed: f32,
+    /// Particles per simulated second
+    rate: f32,
+    material: usize,
+    colour: u32,
+    /// Width of the nozzle, across `direction`
+    width: f32,
+    /// Fraction of a particle owed from earlier steps
+    pending: f32,
+    emitted: u32,
+}
+
+#[wasm_bindgen]
+impl Emitter {
+    pub fn new(
+        x: f32,
+        y: f32,
+        direction_x: f32,
+        direction_y: f32,
+        speed: f32,
+        rate: f32,
+        material: usize,
+    ) -> Self {
+        Self {
+            position: Vec2::new(x, y),
+            direction: Vec2::new(direction_x, direction_y).normalize_or_zero(),
+            speed,
+            rate: valid_rate(rate),
+            material,
+            colour: 0xffffffff,
+            width: 0.02,
+            pending: 0.0,
+            emitted: 0,
+        }
+    }
+
+    /// Particles per simulated second. Negative or non-finite rates are
+    /// stored as zero.
+    #[wasm_bindgen(getter)]
+    pub fn rate(&self) -> f32 {
+        self.rate
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_rate(&mut self, rate: f32) {
+        self.rate = valid_rate(rate);
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn speed(&self) -> f32 {
+        self.speed
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_speed(&mut self, speed: f32) {
+        self.speed = speed;
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn material(&self) -> usize {
+        self.material
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn colour(&self) -> u32 {
+        self.colour
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_colour(&mut self, colour: u32) {
+        self.colour = colour;
+    }
+
+    /// Width of the stream, in world units
+    #[wasm_bindgen(getter)]
+    pub fn width(&self) -> f32 {
+        self.width
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_width(&mut self, width: f32) {
+        self.width = width;
+    }
+
+    pub fn set_position(&mut self, x: f32, y: f32) {
+        self.position = Vec2::new(x, y);
+    }
+
+    pub fn set_direction(&mut self, direction_x: f32, direction_y: f32) {
+        self.direction = Vec2::new(direction_x, direction_y).normalize_or_zero();
+    }
+}
+
+impl Emitter {
+    /// Adds the particles due over the next `dt` seconds to `particles`.
+    pub fn emit(&mut self, dt: f32, particles: &mut Vec<Particle>) {
+        self.pending += valid_rate(self.rate) * dt;
+        if !self.pending.is_finite() {
+            self.pending = 0.0;
+        }
+        self.pending = self.pending.min(MAX_PER_STEP);
+
+        let across = self.direction.perp();
+        while self.pending >= 1.0 {
+            self.pending -= 1.0;
+
+            let offset = (self.emitted as f32 * GOLDEN_FRACTION).fract() - 0.5;
+            self.emitted = self.emitted.wrapping_add(1);
+
+            let mut particle = Particle::new(
+                self.position + offset * self.width * across,
+                self.colour,
+                self.material,
+            );
+            particle.velocity = self.speed * self.direction;
+            particles.push(particle);
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, serde::Serialize, serde::Deserialize)]
+enum Region {
+    Rectangle { min: Vec2, max: Vec2 },
+    Circle { center: Vec2, radius: f32 },
+}
+
+/// A region that deletes any particle entering it, like a drain.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, serde::Serialize, serde::Deserialize)]
+pub struct Sink {
+    region: Region,
+}
+
+#[wasm_bindgen]
+impl Sink {
+    pub fn rectangle(min_x: f32, min_y: f32, max_x: f32, max_y: f32) -> Self {
+        Self {
+            region: Region::Rectangle {
+                min: Vec2::new(min_x, min_y),
+                max: Vec2::new(max_x, max_y),
+            },
+        }
+    }
+
+    pub fn circle(x: f32, y: f32, radius: f32) -> Self {
+        Self {
+            region: Region::Circle {
+                center: Vec2::new(x, y),
+                radius,
+            },
+        }
+    }
+}
+
+impl Sink {
+    pub fn contains(&self, position: Vec2) -> bool {
+        match self.region {
+            Region::Rectangle { min, max } => {
+                position.cmpge(min).all() && position.cmple(max).all()
+            }
+            Region::Circle { center, radius } => {
+                position.distance_squared(center) <= radius * radius
+            }
+        }
+    }
+}
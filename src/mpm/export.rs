@@ -0,0 +1,134 @@
+use glam::IVec2;
+use std::fmt::Write;
+
+use super::{MpmSolver, Particle};
+
+/// Reads one attribute of a particle
+type Attribute = fn(&Particle) -> f32;
+
+/// Scalar attributes written for every particle, by name. `J` is the
+/// determinant of the current deformation gradient, and
+/// `deformation_gradient_det` is the plastic volume ratio that drives
+/// hardening.
+const PARTICLE_SCALARS: [(&str, Attribute); 2] = [
+    ("J", |p| p.deformation_gradient.determinant()),
+    ("deformation_gradient_det", |p| p.deformation_gradient_det),
+];
+
+impl MpmSolver {
+    /// Particles as an ASCII legacy VTK polydata file, for ParaView and
+    /// other VTK readers. Each particle is a vertex with its velocity, `J`,
+    /// `deformation_gradient_det` and material id as point data. Points sit
+    /// at z = 0.
+    pub fn export_vtk(&self) -> String {
+        let n = self.particles.len();
+        let mut out = String::new();
+
+        vtk_header(&mut out, "MPM particles");
+        writeln!(out, "DATASET POLYDATA").unwrap();
+        writeln!(out, "POINTS {} float", n).unwrap();
+        for p in &self.particles {
+            writeln!(out, "{} {} 0", p.position.x, p.position.y).unwrap();
+        }
+
+        writeln!(out, "VERTICES {} {}", n, 2 * n).unwrap();
+        for i in 0..n {
+            writeln!(out, "1 {}", i).unwrap();
+        }
+
+        writeln!(out, "POINT_DATA {}", n).unwrap();
+        writeln!(out, "VECTORS velocity float").unwrap();
+        for p in &self.particles {
+            writeln!(out, "{} {} 0", p.velocity.x, p.velocity.y).unwrap();
+        }
+        for (name, value) in PARTICLE_SCALARS {
+            writeln!(out, "SCALARS {} float 1", name).unwrap();
+            writeln!(out, "LOOKUP_TABLE default").unwrap();
+            for p in &self.particles {
+                writeln!(out, "{}", value(p)).unwrap();
+            }
+        }
+        writeln!(out, "SCALARS material int 1").unwrap();
+        writeln!(out, "LOOKUP_TABLE default").unwrap();
+        for p in &self.particles {
+            writeln!(out, "{}", p.material).unwrap();
+        }
+
+        out
+    }
+
+    /// Particles as a binary little-endian PLY file, for Blender and other
+    /// point cloud tools. Vertices carry `x`, `y`, `z` (always 0), `vx`,
+    /// `vy`, `J`, `deformation_gradient_det` and `material`.
+    pub fn export_ply(&self) -> Vec<u8> {
+        let mut header = String::new();
+        writeln!(header, "ply").unwrap();
+        writeln!(header, "format binary_little_endian 1.0").unwrap();
+        writeln!(header, "comment MPM particles").unwrap();
+        writeln!(header, "element vertex {}", self.particles.len()).unwrap();
+        for name in ["x", "y", "z", "vx", "vy"] {
+            writeln!(header, "property float {}", name).unwrap();
+        }
+        for (name, _) in PARTICLE_SCALARS {
+            writeln!(header, "property float {}", name).unwrap();
+        }
+        writeln!(header, "property int material").unwrap();
+        writeln!(header, "end_header").unwrap();
+
+        let mut bytes = header.into_bytes();
+        for p in &self.particles {
+            for v in [p.position.x, p.position.y, 0.0, p.velocity.x, p.velocity.y] {
+                bytes.extend_from_slice(&v.to_le_bytes());
+            }
+            for (_, value) in PARTICLE_SCALARS {
+                bytes.extend_from_slice(&value(p).to_le_bytes());
+            }
+            bytes.extend_from_slice(&(p.material as i32).to_le_bytes());
+        }
+
+        bytes
+    }
+
+    /// Grid mass and velocity from the last step as an ASCII legacy VTK
+    /// structured points file, one point per grid node. Nodes no particle
+    /// reached have zero mass and velocity.
+    pub fn export_grid_vtk(&self) -> String {
+        let nodes = IVec2::new(self.domain.grid_x() as i32, self.domain.grid_y() as i32) + 1;
+        let dx = self.domain.cell_size();
+        let origin = self.domain.origin();
+        let mut out = String::new();
+
+        vtk_header(&mut out, "MPM grid");
+        writeln!(out, "DATASET STRUCTURED_POINTS").unwrap();
+        writeln!(out, "DIMENSIONS {} {} 1", nodes.x, nodes.y).unwrap();
+        writeln!(out, "ORIGIN {} {} 0", origin.x, origin.y).unwrap();
+        writeln!(out, "SPACING {} {} 1", dx, dx).unwrap();
+
+        // VTK orders points with x varying fastest
+        let coords = || (0..nodes.y).flat_map(move |y| (0..nodes.x).map(move |x| IVec2::new(x, y)));
+
+        writeln!(out, "POINT_DATA {}", nodes.x * nodes.y).unwrap();
+        writeln!(out, "SCALARS mass float 1").unwrap();
+        writeln!(out, "LOOKUP_TABLE default").unwrap();
+        for coord in coords() {
+            writeln!(out, "{}", self.grid[coord].mass).unwrap();
+        }
+        writeln!(out, "VECTORS velocity float").unwrap();
+        for coord in coords() {
+            let cell = &self.grid[coord];
+            if cell.mass > 0.0 {
+                writeln!(out, "{} {} 0", cell.velocity.x, cell.velocity.y).unwrap();
+            } else {
+                writeln!(out, "0 0 0").unwrap();
+            }
+        }
+
+        out
+    }
+}
+
+fn vtk_header(out: &mut String, title: &str) {
+    writeln!(out, "# vtk DataFile Version 3.0").unwrap();
+    writeln!(out, "{}", title).unwrap();
+    writeln!(out, "ASCII").unwrap();
+}
@@ -0,0 +1,254 @@
+use glam::IVec2;
+use std::ops::{Index, IndexMut};
+
+use super::Vec2;
+
+/// Nodes per side of a grid block
+const BLOCK_SIZE: i32 = 4;
+const BLOCK_CELLS: usize = (BLOCK_SIZE * BLOCK_SIZE) as usize;
+
+/// Block table entry for blocks that have not been touched this step
+const INACTIVE: u32 = u32::MAX;
+
+#[derive(Clone, Debug)]
+pub(crate) struct Cell {
+    pub velocity: Vec2,
+    pub mass: f32,
+}
+
+impl Cell {
+    const EMPTY: Cell = Cell {
+        velocity: Vec2::ZERO,
+        mass: 0.0,
+    };
+}
+
+impl Default for Cell {
+    fn default() -> Self {
+        Self::EMPTY
+    }
+}
+
+type Block = [Cell; BLOCK_CELLS];
+
+/// Background grid of `(grid_x + 1) * (grid_y + 1)` nodes, stored sparsely as square
+/// blocks that are only allocated where particles touch them.
+///
+/// Writing to a node activates its block. Reading a node in an inactive block
+/// gives an empty cell. Blocks are pooled, so after the first few steps no
+/// allocation happens at all.
+pub(crate) struct Grid {
+    /// Nodes along each axis
+    size: IVec2,
+    /// Blocks along y, the stride of the block table
+    blocks_y: i32,
+    /// Index into `blocks` for every block of the domain, or `INACTIVE`
+    block_table: Vec<u32>,
+    /// Block storage. The first `active.len()` entries are in use.
+    blocks: Vec<Block>,
+    /// Coordinates of the blocks in use, in block units, parallel to `blocks`
+    active: Vec<IVec2>,
+}
+
+impl Grid {
+    pub fn new(grid_x: usize, grid_y: usize) -> Self {
+        let size = IVec2::new(grid_x as i32, grid_y as i32) + IVec2::ONE;
+        let blocks = (size + IVec2::splat(BLOCK_SIZE - 1)) / BLOCK_SIZE;
+
+        Self {
+            size,
+            blocks_y: blocks.y,
+            block_table: vec![INACTIVE; (blocks.x * blocks.y) as usize],
+            blocks: vec![],
+            active: vec![],
+        }
+    }
+
+    /// Deactivates every block, keeping their storage for reuse.
+    pub fn clear(&mut self) {
+        for block in self.active.drain(..) {
+            self.block_table[(block.x * self.blocks_y + block.y) as usize] = INACTIVE;
+        }
+    }
+
+    pub fn active_blocks(&self) -> usize {
+        self.active.len()
+    }
+
+    /// Visits every node of the active blocks along with its coordinate.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (IVec2, &mut Cell)> {
+        let size = self.size;
+
+        self.active
+            .iter()
+            .zip(self.blocks.iter_mut())
+            .flat_map(move |(&block, cells)| {
+                cells
+                    .iter_mut()
+                    .enumerate()
+                    .filter_map(move |(index, cell)| {
+                        let index = index as i32;
+                        let coord =
+                            block * BLOCK_SIZE + IVec2::new(index / BLOCK_SIZE, index % BLOCK_SIZE);
+
+                        // Blocks on the far edges overhang the domain
+                        (coord.x < size.x && coord.y < size.y).then_some((coord, cell))
+                    })
+            })
+    }
+
+    /// Position of `coord` as (block table index, index within the block).
+    fn locate(&self, coord: IVec2) -> (usize, usize) {
+        assert!(
+            coord.x >= 0 && coord.y >= 0 && coord.x < self.size.x && coord.y < self.size.y,
+            "Grid coordinate {} out of range",
+            coord
+        );
+
+        let block = coord / BLOCK_SIZE;
+        let local = coord % BLOCK_SIZE;
+
+        (
+            (block.x * self.blocks_y + block.y) as usize,
+            (local.x * BLOCK_SIZE + local.y) as usize,
+        )
+    }
+
+    /// Returns the pool index of the block at `table_index`, activating it
+    /// if needed.
+    fn activate(&mut self, table_index: usize) -> usize {
+        let entry = self.block_table[table_index];
+        if entry != INACTIVE {
+            return entry as usize;
+        }
+
+        let index = self.active.len();
+        let block = IVec2::new(
+            table_index as i32 / self.blocks_y,
+            table_index as i32 % self.blocks_y,
+        );
+
+        if index < self.blocks.len() {
+            self.blocks[index] = Default::default();
+        } else {
+            self.blocks.push(Default::default());
+        }
+        self.active.push(block);
+        self.block_table[table_index] = index as u32;
+
+        index
+    }
+}
+
+impl Default for Grid {
+    /// A grid with a single node, to be replaced once the domain is known
+    fn default() -> Self {
+        Self::new(0, 0)
+    }
+}
+
+impl Index<IVec2> for Grid {
+    type Output = Cell;
+
+    fn index(&self, coord: IVec2) -> &Cell {
+        let (table_index, cell_index) = self.locate(coord);
+
+        match self.block_table[table_index] {
+            INACTIVE => &Cell::EMPTY,
+            block => &self.blocks[block as usize][cell_index],
+        }
+    }
+}
+
+impl IndexMut<IVec2> for Grid {
+    fn index_mut(&mut self, coord: IVec2) -> &mut Cell {
+        let (table_index, cell_index) = self.locate(coord);
+        let block = self.activate(table_index);
+
+        &mut self.blocks[block][cell_index]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mpm::Kernel;
+
+    const GRID_X: usize = 13;
+    const GRID_Y: usize = 9;
+
+    /// Scatters mass and momentum from a few particles the way P2G does.
+    fn scatter(positions: &[Vec2], mut write: impl FnMut(IVec2, Vec2, f32)) {
+        for (i, &position) in positions.iter().enumerate() {
+            let velocity = Vec2::new(i as f32, 1.0 - i as f32);
+            for node in Kernel::Quadratic.stencil(position).nodes(1.0) {
+                write(node.coord, velocity * node.weight, node.weight);
+            }
+        }
+    }
+
+    #[test]
+    fn matches_a_dense_grid() {
+        let positions = [
+            Vec2::new(1.7, 1.2),
+            Vec2::new(3.5, 4.5),
+            Vec2::new(11.1, 7.9),
+            Vec2::new(11.3, 7.6),
+        ];
+
+        let mut grid = Grid::new(GRID_X, GRID_Y);
+        scatter(&positions, |coord, momentum, mass| {
+            let cell = &mut grid[coord];
+            cell.velocity += momentum;
+            cell.mass += mass;
+        });
+
+        let nodes_y = GRID_Y + 1;
+        let mut dense = vec![Cell::default(); (GRID_X + 1) * nodes_y];
+        scatter(&positions, |coord, momentum, mass| {
+            let cell = &mut dense[coord.x as usize * nodes_y + coord.y as usize];
+            cell.velocity += momentum;
+            cell.mass += mass;
+        });
+
+        for x in 0..=GRID_X {
+            for y in 0..=GRID_Y {
+                let coord = IVec2::new(x as i32, y as i32);
+                let expected = &dense[x * nodes_y + y];
+                assert_eq!(grid[coord].mass, expected.mass, "{}", coord);
+                assert_eq!(grid[coord].velocity, expected.velocity, "{}", coord);
+            }
+        }
+
+        // Every node with mass is visited exactly once
+        let mut visited = 0;
+        for (coord, cell) in grid.iter_mut() {
+            assert_eq!(
+                cell.mass,
+                dense[coord.x as usize * nodes_y + coord.y as usize].mass
+            );
+            visited += (cell.mass > 0.0) as usize;
+        }
+        assert_eq!(visited, dense.iter().filter(|c| c.mass > 0.0).count());
+    }
+
+    #[test]
+    fn clear_reuses_blocks() {
+        let mut grid = Grid::new(GRID_X, GRID_Y);
+        grid[IVec2::new(1, 1)].mass = 1.0;
+        grid[IVec2::new(12, 8)].mass = 1.0;
+        assert_eq!(grid.active_blocks(), 2);
+
+        grid.clear();
+        assert_eq!(grid.active_blocks(), 0);
+        assert_eq!(grid[IVec2::new(1, 1)].mass, 0.0);
+
+        // Different blocks, but no new storage, and handed out zeroed
+        grid[IVec2::new(5, 5)].mass += 2.0;
+        grid[IVec2::new(9, 1)].mass += 2.0;
+        assert_eq!(grid.active_blocks(), 2);
+        assert_eq!(grid.blocks.len(), 2);
+        assert_eq!(grid[IVec2::new(5, 5)].mass, 2.0);
+        assert_eq!(grid[IVec2::new(12, 8)].mass, 0.0);
+    }
+}
@@ -0,0 +1,163 @@
+use crate::linalg::square_vec;
+use glam::IVec2;
+use wasm_bindgen::prelude::*;
+
+use super::Vec2;
+
+/// The widest stencil of any kernel, per axis
+const MAX_STENCIL: usize = 4;
+
+/// B-spline interpolation kernel used for particle/grid transfers.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum Kernel {
+    /// 2x2 stencil. Cheapest, but its discontinuous gradient makes stiff
+    /// materials prone to cell-crossing instability
+    Linear,
+    /// 3x3 stencil
+    Quadratic,
+    /// 4x4 stencil. Smoothest, with the least cell-crossing noise
+    Cubic,
+}
+
+impl Kernel {
+    /// Number of grid nodes a particle touches along each axis
+    pub fn stencil_size(self) -> usize {
+        match self {
+            Kernel::Linear => 2,
+            Kernel::Quadratic => 3,
+            Kernel::Cubic => 4,
+        }
+    }
+
+    /// Offset from a particle to the first node of its stencil, in cells
+    fn offset(self) -> f32 {
+        match self {
+            Kernel::Linear => 0.0,
+            Kernel::Quadratic => 0.5,
+            Kernel::Cubic => 1.0,
+        }
+    }
+
+    /// Whether every node of the stencil around `position`, given in grid
+    /// cells, lies on a grid with `nodes` nodes along each axis. Checked
+    /// before the stencil is built, so that far-away or non-finite positions
+    /// are rejected rather than overflowing the node indices.
+    pub fn stencil_lies_within(self, position: Vec2, nodes: IVec2) -> bool {
+        let first = position - Vec2::splat(self.offset());
+        let end = (nodes - IVec2::splat(self.stencil_size() as i32 - 1)).as_vec2();
+        first.is_finite() && first.cmpge(Vec2::ZERO).all() && first.cmplt(end).all()
+    }
+
+    /// MLS-MPM's inverse inertia-like tensor, as a multiple of `inv_dx^2`.
+    /// The linear kernel has no constant D, so its transfers use the kernel
+    /// gradient directly instead.
+    pub fn d_inv(self) -> Option<f32> {
+        match self {
+            Kernel::Linear => None,
+            Kernel::Quadratic => Some(4.0),
+            Kernel::Cubic => Some(3.0),
+        }
+    }
+
+    /// Weights and gradients of this kernel around `position`, given in grid
+    /// cells.
+    pub fn stencil(self, position: Vec2) -> Stencil {
+        // Elementwise floor
+        let base = (position - Vec2::splat(self.offset())).floor().as_ivec2();
+        let fx = position - base.as_vec2();
+
+        let mut w = [Vec2::ZERO; MAX_STENCIL];
+        let mut dw = [Vec2::ZERO; MAX_STENCIL];
+
+        match self {
+            Kernel::Linear => {
+                w[0] = Vec2::ONE - fx;
+                w[1] = fx;
+
+                dw[0] = Vec2::splat(-1.0);
+                dw[1] = Vec2::ONE;
+            }
+            Kernel::Quadratic => {
+                w[0] = Vec2::splat(0.5) * square_vec(Vec2::splat(1.5) - fx);
+                w[1] = Vec2::splat(0.75) - square_vec(fx - Vec2::ONE);
+                w[2] = Vec2::splat(0.5) * square_vec(fx - Vec2::splat(0.5));
+
+                dw[0] = fx - Vec2::splat(1.5);
+                dw[1] = -2.0 * (fx - Vec2::ONE);
+                dw[2] = fx - Vec2::splat(0.5);
+            }
+            Kernel::Cubic => {
+                let d1 = fx - Vec2::ONE; // Distance to node 1, in [0, 1)
+                let d2 = Vec2::splat(2.0) - fx; // Distance to node 2, in (0, 1]
+
+                w[0] = square_vec(d2) * d2 / 6.0;
+                w[1] = 0.5 * square_vec(d1) * d1 - square_vec(d1) + Vec2::splat(2.0 / 3.0);
+                w[2] = 0.5 * square_vec(d2) * d2 - square_vec(d2) + Vec2::splat(2.0 / 3.0);
+                w[3] = square_vec(d1) * d1 / 6.0;
+
+                dw[0] = -0.5 * square_vec(d2);
+                dw[1] = 1.5 * square_vec(d1) - 2.0 * d1;
+                dw[2] = -1.5 * square_vec(d2) + 2.0 * d2;
+                dw[3] = 0.5 * square_vec(d1);
+            }
+        }
+
+        Stencil {
+            kernel: self,
+            base,
+            fx,
+            w,
+            dw,
+        }
+    }
+}
+
+/// Kernel weights of one particle against the grid nodes around it.
+pub struct Stencil {
+    kernel: Kernel,
+    base: IVec2,
+    fx: Vec2,
+    w: [Vec2; MAX_STENCIL],
+    dw: [Vec2; MAX_STENCIL],
+}
+
+pub struct StencilNode {
+    pub coord: IVec2,
+    pub weight: f32,
+    /// Node position relative to the particle, in grid cells
+    pub dpos: Vec2,
+    /// Gradient of the weight, in world units
+    pub gradient: Vec2,
+    /// What the transfers use in place of the gradient: MLS-MPM's
+    /// `D^-1 * (x_i - x_p) * w` where the kernel has a D, else the gradient
+    pub mls_gradient: Vec2,
+}
+
+impl Stencil {
+    pub fn nodes(&self, inv_dx: f32) -> impl Iterator<Item = StencilNode> + '_ {
+        let size = self.kernel.stencil_size();
+
+        (0..size).flat_map(move |i| {
+            (0..size).map(move |j| {
+                let dpos = Vec2::new(i as f32, j as f32) - self.fx;
+                let weight = self.w[i].x * self.w[j].y;
+
+                let gradient =
+                    inv_dx * Vec2::new(self.dw[i].x * self.w[j].y, self.w[i].x * self.dw[j].y);
+                let mls_gradient = match self.kernel.d_inv() {
+                    Some(d_inv) => d_inv * inv_dx * weight * dpos,
+                    None => gradient,
+                };
+
+                StencilNode {
+                    coord: self.base + IVec2::new(i as i32, j as i32),
+                    weight,
+                    dpos,
+                    gradient,
+                    mls_gradient,
+                }
+            })
+        })
+    }
+}
@@ -0,0 +1,544 @@
+use super::{Mat2, Particle, Vec2};
+use crate::linalg::{polar_decomp, svd};
+use wasm_bindgen::prelude::*;
+
+// Drucker-Prager friction hardening, from Klár et al. 2016. The friction
+// angle starts `H3` below the material's and approaches it as the particle
+// accumulates plastic strain.
+const DP_H1: f32 = 9.0; // Degrees
+const DP_H2: f32 = 0.2;
+const DP_H3: f32 = 10.0; // Degrees
+
+/// Whether a material resists shear at all.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum Phase {
+    /// Keeps the full deformation gradient and responds with elastic stress
+    Solid,
+    /// Weakly compressible liquid, only its volume change is tracked
+    Fluid,
+}
+
+/// Elastic energy a solid material's stress is derived from.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum ConstitutiveModel {
+    /// Fixed corotated, as in Stomakhin et al. 2012
+    FixedCorotated,
+    /// Compressible Neo-Hookean
+    NeoHookean,
+    /// St. Venant-Kirchhoff with Hencky strain, which is what the
+    /// Drucker-Prager return mapping is defined against
+    Hencky,
+}
+
+/// How a material yields once it is deformed past its elastic limit.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum PlasticityModel {
+    /// Purely elastic, always returns to its rest shape
+    None,
+    /// Clamps the singular values of F, as in Stomakhin et al. 2013
+    Snow,
+    /// Granular return mapping in log-strain space, as in Klár et al. 2016
+    DruckerPrager,
+    /// J2 return mapping in log-strain space, for metals that bend and dent
+    VonMises,
+}
+
+/// Properties of one material. Particles refer to a material by its index
+/// in the solver's material list.
+///
+/// The Lamé parameters are derived from the Young's modulus and Poisson
+/// ratio, and are recomputed whenever either of those is changed.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, serde::Serialize, serde::Deserialize)]
+pub struct MaterialParams {
+    phase: Phase,
+    model: ConstitutiveModel,
+    particle_mass: f32,
+    volume: f32,
+    hardening: f32,
+    youngs_modulus: f32,
+    poisson_ratio: f32,
+    plasticity: PlasticityModel,
+    friction_angle: f32,
+    cohesion: f32,
+    yield_stress: f32,
+    bulk_modulus: f32,
+    gamma: f32,
+    viscosity: f32,
+    mu_0: f32,
+    lambda_0: f32,
+}
+
+#[wasm_bindgen]
+impl MaterialParams {
+    pub fn new(
+        particle_mass: f32,
+        volume: f32,
+        hardening: f32,
+        youngs_modulus: f32,
+        poisson_ratio: f32,
+        plasticity: PlasticityModel,
+    ) -> Self {
+        let mut params = Self {
+            phase: Phase::Solid,
+            model: ConstitutiveModel::FixedCorotated,
+            particle_mass,
+            volume,
+            hardening,
+            youngs_modulus,
+            poisson_ratio,
+            plasticity,
+            friction_angle: 35.0,
+            cohesion: 0.0,
+            yield_stress: 150.0,
+            bulk_modulus: 5000.0,
+            gamma: 7.0,
+            viscosity: 0.0,
+            mu_0: 0.0,
+            lambda_0: 0.0,
+        };
+        params.update_lame_parameters();
+        params
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn phase(&self) -> Phase {
+        self.phase
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_phase(&mut self, phase: Phase) {
+        self.phase = phase;
+    }
+
+    /// Stress model used by the solid phase
+    #[wasm_bindgen(getter)]
+    pub fn model(&self) -> ConstitutiveModel {
+        self.model
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_model(&mut self, model: ConstitutiveModel) {
+        self.model = model;
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn particle_mass(&self) -> f32 {
+        self.particle_mass
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_particle_mass(&mut self, particle_mass: f32) {
+        self.particle_mass = particle_mass;
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn volume(&self) -> f32 {
+        self.volume
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_volume(&mut self, volume: f32) {
+        self.volume = volume;
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn hardening(&self) -> f32 {
+        self.hardening
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_hardening(&mut self, hardening: f32) {
+        self.hardening = hardening;
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn youngs_modulus(&self) -> f32 {
+        self.youngs_modulus
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_youngs_modulus(&mut self, youngs_modulus: f32) {
+        self.youngs_modulus = youngs_modulus;
+        self.update_lame_parameters();
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn poisson_ratio(&self) -> f32 {
+        self.poisson_ratio
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_poisson_ratio(&mut self, poisson_ratio: f32) {
+        self.poisson_ratio = poisson_ratio;
+        self.update_lame_parameters();
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn plasticity(&self) -> PlasticityModel {
+        self.plasticity
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_plasticity(&mut self, plasticity: PlasticityModel) {
+        self.plasticity = plasticity;
+    }
+
+    /// Drucker-Prager friction angle, in degrees
+    #[wasm_bindgen(getter)]
+    pub fn friction_angle(&self) -> f32 {
+        self.friction_angle
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_friction_angle(&mut self, friction_angle: f32) {
+        self.friction_angle = friction_angle;
+    }
+
+    /// Drucker-Prager cohesion, as the volumetric log-strain the material
+    /// can sustain in tension before it falls apart
+    #[wasm_bindgen(getter)]
+    pub fn cohesion(&self) -> f32 {
+        self.cohesion
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_cohesion(&mut self, cohesion: f32) {
+        self.cohesion = cohesion;
+    }
+
+    /// Von Mises yield stress, as the norm of the deviatoric Kirchhoff stress
+    /// the material can sustain before flowing
+    #[wasm_bindgen(getter)]
+    pub fn yield_stress(&self) -> f32 {
+        self.yield_stress
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_yield_stress(&mut self, yield_stress: f32) {
+        self.yield_stress = yield_stress;
+    }
+
+    /// Fluid bulk modulus, the stiffness of the equation of state
+    #[wasm_bindgen(getter)]
+    pub fn bulk_modulus(&self) -> f32 {
+        self.bulk_modulus
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_bulk_modulus(&mut self, bulk_modulus: f32) {
+        self.bulk_modulus = bulk_modulus;
+    }
+
+    /// Fluid equation of state exponent
+    #[wasm_bindgen(getter)]
+    pub fn gamma(&self) -> f32 {
+        self.gamma
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_gamma(&mut self, gamma: f32) {
+        self.gamma = gamma;
+    }
+
+    /// Fluid dynamic viscosity, zero for an inviscid fluid
+    #[wasm_bindgen(getter)]
+    pub fn viscosity(&self) -> f32 {
+        self.viscosity
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_viscosity(&mut self, viscosity: f32) {
+        self.viscosity = viscosity;
+    }
+
+    /// Initial shear modulus
+    #[wasm_bindgen(getter)]
+    pub fn mu_0(&self) -> f32 {
+        self.mu_0
+    }
+
+    /// Initial first Lamé parameter
+    #[wasm_bindgen(getter)]
+    pub fn lambda_0(&self) -> f32 {
+        self.lambda_0
+    }
+
+    /// Dry sand with Drucker-Prager plasticity
+    pub fn sand() -> Self {
+        Self {
+            model: ConstitutiveModel::Hencky,
+            ..Self::new(1.0, 1.0, 0.0, 10000.0, 0.3, PlasticityModel::DruckerPrager)
+        }
+    }
+
+    /// Ductile metal with von Mises plasticity
+    pub fn metal() -> Self {
+        Self {
+            model: ConstitutiveModel::Hencky,
+            ..Self::new(1.0, 1.0, 0.0, 10000.0, 0.3, PlasticityModel::VonMises)
+        }
+    }
+
+    /// Weakly compressible water
+    pub fn water() -> Self {
+        Self {
+            phase: Phase::Fluid,
+            ..Self::default()
+        }
+    }
+
+    fn update_lame_parameters(&mut self) {
+        let e = self.youngs_modulus;
+        let nu = self.poisson_ratio;
+        self.mu_0 = e / (2.0 * (1.0 + nu));
+        self.lambda_0 = e * nu / ((1.0 + nu) * (1.0 - 2.0 * nu));
+    }
+}
+
+impl Default for MaterialParams {
+    // Snow material properties
+    fn default() -> Self {
+        Self::new(1.0, 1.0, 10.0, 10000.0, 0.2, PlasticityModel::None)
+    }
+}
+
+impl MaterialParams {
+    /// Speed of the fastest wave through this material at the particle's
+    /// current state: pressure waves for fluids, P-waves for solids.
+    #[allow(non_snake_case)]
+    pub(crate) fn wave_speed(&self, particle: &Particle) -> f32 {
+        let density = self.particle_mass / self.volume;
+
+        let stiffness = if self.phase == Phase::Fluid {
+            let J = particle.deformation_gradient.determinant();
+            self.bulk_modulus * J.powf(-self.gamma)
+        } else {
+            let e = (self.hardening * (1.0 - particle.deformation_gradient_det)).exp();
+            e * (self.lambda_0 + 2.0 * self.mu_0)
+        };
+
+        (stiffness / density).sqrt()
+    }
+
+    pub(crate) fn kirchhoff_stress(&self, particle: &Particle) -> Mat2 {
+        if self.phase == Phase::Fluid {
+            return self.fluid_stress(particle);
+        }
+
+        // Lamé parameters, hardened by the particle's plastic volume change
+        let e = (self.hardening * (1.0 - particle.deformation_gradient_det)).exp();
+        let mu = self.mu_0 * e;
+        let lambda = self.lambda_0 * e;
+
+        let f = particle.deformation_gradient;
+
+        match self.model {
+            ConstitutiveModel::FixedCorotated => fixed_corotated_stress(f, mu, lambda),
+            ConstitutiveModel::NeoHookean => neo_hookean_stress(f, mu, lambda),
+            ConstitutiveModel::Hencky => hencky_stress(f, mu, lambda),
+        }
+    }
+
+    /// Tait equation of state pressure plus Newtonian viscous stress, both
+    /// scaled by J to give Kirchhoff stress.
+    #[allow(non_snake_case)]
+    fn fluid_stress(&self, particle: &Particle) -> Mat2 {
+        let J = particle.deformation_gradient.determinant();
+        let pressure = self.bulk_modulus / self.gamma * (J.powf(-self.gamma) - 1.0);
+
+        // The APIC affine matrix approximates the velocity gradient
+        let c = particle.apic_affine_momentum;
+        let strain_rate = 0.5 * (c + c.transpose());
+
+        J * (2.0 * self.viscosity * strain_rate - pressure * Mat2::IDENTITY)
+    }
+
+    /// Projects the trial deformation gradient `F` back onto the yield
+    /// surface and stores the result on the particle.
+    #[allow(non_snake_case)]
+    pub(crate) fn update_deformation_gradient(&self, particle: &mut Particle, F: Mat2) {
+        if self.phase == Phase::Fluid {
+            // Only the volume change matters, so drop the deviatoric part
+            let J = F.determinant().max(0.1);
+            particle.deformation_gradient = Mat2::IDENTITY * J.sqrt();
+            return;
+        }
+
+        let (svd_u, mut sig, svd_v) = svd(F);
+
+        match self.plasticity {
+            PlasticityModel::None => {}
+            PlasticityModel::Snow => {
+                sig.col_mut(0).x = sig.col_mut(0).x.clamp(1.0 - 2.5e-2, 1.0 + 7.5e-3);
+                sig.col_mut(1).y = sig.col_mut(1).y.clamp(1.0 - 2.5e-2, 1.0 + 7.5e-3);
+
+                let old_j = F.determinant();
+                let new_j = sig.col(0).x * sig.col(1).y;
+
+                particle.deformation_gradient_det =
+                    (particle.deformation_gradient_det * old_j / new_j).clamp(0.6, 20.0);
+            }
+            PlasticityModel::DruckerPrager => {
+                let projected =
+                    self.drucker_prager_projection(particle, Vec2::new(sig.col(0).x, sig.col(1).y));
+                sig = Mat2::from_diagonal(projected);
+            }
+            PlasticityModel::VonMises => {
+                let projected =
+                    self.von_mises_projection(particle, Vec2::new(sig.col(0).x, sig.col(1).y));
+                sig = Mat2::from_diagonal(projected);
+            }
+        }
+
+        particle.deformation_gradient = svd_u * sig * svd_v.transpose();
+    }
+
+    /// Return mapping onto the von Mises cylinder for the singular values
+    /// `sig` of the trial deformation gradient. Only the deviatoric part of
+    /// the log strain is projected, so plastic flow preserves volume.
+    fn von_mises_projection(&self, particle: &mut Particle, sig: Vec2) -> Vec2 {
+        let eps = Vec2::new(sig.x.ln(), sig.y.ln());
+        let eps_hat = eps - Vec2::splat((eps.x + eps.y) / 2.0);
+        let eps_hat_norm = eps_hat.length();
+
+        let delta_gamma = eps_hat_norm - self.yield_stress / (2.0 * self.mu_0);
+
+        if delta_gamma <= 0.0 {
+            // Inside the yield surface
+            return sig;
+        }
+
+        particle.plastic_strain += delta_gamma;
+
+        let h = eps - delta_gamma * eps_hat / eps_hat_norm;
+        Vec2::new(h.x.exp(), h.y.exp())
+    }
+
+    /// Return mapping onto the Drucker-Prager cone for the singular values
+    /// `sig` of the trial deformation gradient. Updates the particle's
+    /// hardening and volume correction state.
+    fn drucker_prager_projection(&self, particle: &mut Particle, sig: Vec2) -> Vec2 {
+        const DIM: f32 = 2.0;
+
+        let friction_angle = self.friction_angle
+            + (DP_H1 * particle.plastic_strain - DP_H3) * (-DP_H2 * particle.plastic_strain).exp();
+        let sin_phi = friction_angle.to_radians().sin();
+        let alpha = (2.0f32 / 3.0).sqrt() * 2.0 * sin_phi / (3.0 - sin_phi);
+
+        // The volume correction lets material that expanded while separating
+        // recover that volume before it resists compression again
+        let eps = Vec2::new(sig.x.ln(), sig.y.ln()) + Vec2::splat(particle.volume_correction / DIM);
+        let trace = eps.x + eps.y - self.cohesion;
+        let eps_hat = eps - Vec2::splat((eps.x + eps.y) / DIM);
+        let eps_hat_norm = eps_hat.length();
+
+        if trace >= 0.0 {
+            // Tension: project to the tip of the cone
+            particle.plastic_strain += (eps - Vec2::splat(self.cohesion / DIM)).length();
+            particle.volume_correction = trace;
+            return Vec2::splat((self.cohesion / DIM).exp());
+        }
+
+        particle.volume_correction = 0.0;
+
+        let delta_gamma = eps_hat_norm
+            + (DIM * self.lambda_0 + 2.0 * self.mu_0) / (2.0 * self.mu_0) * trace * alpha;
+
+        if delta_gamma <= 0.0 {
+            // Inside the yield surface
+            return Vec2::new(eps.x.exp(), eps.y.exp());
+        }
+
+        particle.plastic_strain += delta_gamma;
+
+        let h = eps - delta_gamma * eps_hat / eps_hat_norm;
+        Vec2::new(h.x.exp(), h.y.exp())
+    }
+}
+
+// Kirchhoff stresses for each constitutive model, given the deformation
+// gradient `F` and the Lamé parameters
+
+#[allow(non_snake_case)]
+fn fixed_corotated_stress(F: Mat2, mu: f32, lambda: f32) -> Mat2 {
+    let J = F.determinant();
+    let (r, _) = polar_decomp(F);
+
+    2.0 * mu * (F - r) * F.transpose() + Mat2::IDENTITY * (lambda * (J - 1.0) * J)
+}
+
+#[allow(non_snake_case)]
+fn neo_hookean_stress(F: Mat2, mu: f32, lambda: f32) -> Mat2 {
+    let J = F.determinant();
+
+    mu * (F * F.transpose() - Mat2::IDENTITY) + Mat2::IDENTITY * (lambda * J.ln())
+}
+
+#[allow(non_snake_case)]
+fn hencky_stress(F: Mat2, mu: f32, lambda: f32) -> Mat2 {
+    let (svd_u, sig, _) = svd(F);
+
+    let eps = Vec2::new(sig.col(0).x.ln(), sig.col(1).y.ln());
+    let tau = 2.0 * mu * eps + Vec2::splat(lambda * (eps.x + eps.y));
+
+    svd_u * Mat2::from_diagonal(tau) * svd_u.transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MU: f32 = 4000.0;
+    const LAMBDA: f32 = 3000.0;
+
+    type Stress = fn(Mat2, f32, f32) -> Mat2;
+    const MODELS: [(&str, Stress); 3] = [
+        ("fixed corotated", fixed_corotated_stress),
+        ("neo-Hookean", neo_hookean_stress),
+        ("Hencky", hencky_stress),
+    ];
+
+    fn norm(m: Mat2) -> f32 {
+        (m.x_axis.length_squared() + m.y_axis.length_squared()).sqrt()
+    }
+
+    #[test]
+    fn rest_shape_has_no_stress() {
+        for (name, stress) in MODELS {
+            assert!(norm(stress(Mat2::IDENTITY, MU, LAMBDA)) < 1e-3, "{}", name);
+        }
+    }
+
+    #[test]
+    fn models_agree_under_small_strain() {
+        // Stretch, shear and squeeze, each rotated so the models see more than
+        // a diagonal F
+        let strains = [
+            Mat2::from_cols(Vec2::new(1e-3, 0.0), Vec2::new(0.0, 0.0)),
+            Mat2::from_cols(Vec2::new(0.0, 5e-4), Vec2::new(5e-4, 0.0)),
+            Mat2::from_cols(Vec2::new(-8e-4, 2e-4), Vec2::new(2e-4, -3e-4)),
+        ];
+
+        for strain in strains {
+            for angle in [0.0, 0.7] {
+                let f = Mat2::from_angle(angle) * (Mat2::IDENTITY + strain);
+                let reference = fixed_corotated_stress(f, MU, LAMBDA);
+
+                for (name, stress) in &MODELS[1..] {
+                    let difference = norm(stress(f, MU, LAMBDA) - reference);
+                    assert!(
+                        difference < 0.01 * norm(reference),
+                        "{} differs by {} from {}",
+                        name,
+                        difference,
+                        reference
+                    );
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,579 @@
+//! Platform-independent MLS-MPM solver.
+//!
+//! Nothing in here touches `web_sys`, so the solver can be built and stepped
+//! natively (tests, benchmarks, offline runs) as well as from the wasm
+//! renderers in `simulations`.
+
+mod boundary;
+mod collider;
+mod domain;
+mod emitter;
+mod export;
+mod grid;
+mod kernel;
+mod material;
+mod params;
+mod pointer;
+mod recording;
+mod rigid_body;
+mod seeding;
+mod state;
+
+use crate::linalg::outer_product;
+use crate::random::{new_rng, SimRng};
+use glam::IVec2;
+use grid::Grid;
+use rand::distributions::{Distribution, Uniform};
+use wasm_bindgen::prelude::*;
+
+pub use crate::linalg::{Mat2, Vec2};
+pub use boundary::{Boundary, BoundaryType, Wall};
+pub use collider::Collider;
+pub use domain::Domain;
+pub use emitter::{Emitter, Sink};
+pub use kernel::{Kernel, Stencil, StencilNode};
+pub use material::{ConstitutiveModel, MaterialParams, Phase, PlasticityModel};
+pub use params::SimParams;
+pub use pointer::{Pointer, PointerMode};
+pub use recording::{Playback, Recorder};
+pub use rigid_body::RigidBody;
+pub use seeding::{Sampling, SeedImage, SeedOptions, SeedShape};
+
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct Particle {
+    pub position: Vec2,
+    pub velocity: Vec2,
+    pub deformation_gradient: Mat2,
+    pub apic_affine_momentum: Mat2,
+    pub deformation_gradient_det: f32,
+    /// Accumulated plastic strain, drives Drucker-Prager friction hardening
+    pub plastic_strain: f32,
+    /// Log volume gained by Drucker-Prager material while in tension
+    pub volume_correction: f32,
+    pub colour: u32,
+    /// Index into the solver's material list
+    pub material: usize,
+}
+
+impl Particle {
+    pub fn new(pos: Vec2, colour: u32, material: usize) -> Self {
+        Self {
+            position: pos,
+            velocity: Vec2::ZERO,
+            deformation_gradient: Mat2::IDENTITY,
+            apic_affine_momentum: Mat2::ZERO,
+            deformation_gradient_det: 1.0,
+            plastic_strain: 0.0,
+            volume_correction: 0.0,
+            colour,
+            material,
+        }
+    }
+}
+
+/// What a call to `MpmSolver::update` did with the wall-clock time it was given.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct UpdateReport {
+    substeps: u32,
+    steps: u32,
+    simulated_time: f32,
+    dropped_time: f32,
+}
+
+#[wasm_bindgen]
+impl UpdateReport {
+    #[wasm_bindgen(getter)]
+    pub fn substeps(&self) -> u32 {
+        self.substeps
+    }
+
+    /// Solver steps taken, more than `substeps` when the CFL limit split them
+    #[wasm_bindgen(getter)]
+    pub fn steps(&self) -> u32 {
+        self.steps
+    }
+
+    /// Simulated seconds advanced
+    #[wasm_bindgen(getter)]
+    pub fn simulated_time(&self) -> f32 {
+        self.simulated_time
+    }
+
+    /// Simulated seconds discarded because the substep cap was reached
+    #[wasm_bindgen(getter)]
+    pub fn dropped_time(&self) -> f32 {
+        self.dropped_time
+    }
+}
+
+/// Everything but the grid, pointer and recorder is saved by `save_state`;
+/// the grid is rebuilt every step anyway, and the others belong to the
+/// user's session.
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct MpmSolver {
+    particles: Vec<Particle>,
+    domain: Domain,
+    #[serde(skip)]
+    grid: Grid,
+    materials: Vec<MaterialParams>,
+    /// Removed colliders leave a `None` so the ids of the rest stay valid
+    colliders: Vec<Option<Collider>>,
+    /// Same id scheme as `colliders`
+    rigid_bodies: Vec<Option<RigidBody>>,
+    #[serde(skip)]
+    pointer: Option<Pointer>,
+    /// Same id scheme as `colliders`
+    emitters: Vec<Option<Emitter>>,
+    sinks: Vec<Option<Sink>>,
+    params: SimParams,
+    /// Drives all seeding, so a seeded solver always starts the same
+    rng: SimRng,
+    last_dt: f32,
+    /// Simulated time owed to `update` but not yet stepped
+    time_accumulator: f32,
+    #[serde(skip)]
+    recorder: Option<Recorder>,
+}
+
+impl MpmSolver {
+    /// Creates an empty solver. Giving a `seed` makes every randomly seeded
+    /// particle reproducible.
+    pub fn new(domain: Domain, params: SimParams, seed: Option<u64>) -> Self {
+        Self {
+            particles: vec![],
+            domain,
+            grid: Grid::new(domain.grid_x(), domain.grid_y()),
+            materials: vec![],
+            colliders: vec![],
+            rigid_bodies: vec![],
+            pointer: None,
+            emitters: vec![],
+            sinks: vec![],
+            params,
+            rng: new_rng(seed),
+            last_dt: 0.0,
+            time_accumulator: 0.0,
+            recorder: None,
+        }
+    }
+
+    pub fn particles(&self) -> &[Particle] {
+        &self.particles
+    }
+
+    pub fn domain(&self) -> &Domain {
+        &self.domain
+    }
+
+    /// Number of grid blocks particles touched in the most recent step.
+    pub fn active_grid_blocks(&self) -> usize {
+        self.grid.active_blocks()
+    }
+
+    pub fn materials(&self) -> &[MaterialParams] {
+        &self.materials
+    }
+
+    pub fn material(&self, id: usize) -> Option<&MaterialParams> {
+        self.materials.get(id)
+    }
+
+    pub fn material_mut(&mut self, id: usize) -> Option<&mut MaterialParams> {
+        self.materials.get_mut(id)
+    }
+
+    /// Registers a material and returns the id particles use to refer to it.
+    pub fn add_material(&mut self, material: MaterialParams) -> usize {
+        self.materials.push(material);
+        self.materials.len() - 1
+    }
+
+    /// Adds an obstacle and returns the id used to remove it.
+    pub fn add_collider(&mut self, collider: Collider) -> usize {
+        self.colliders.push(Some(collider));
+        self.colliders.len() - 1
+    }
+
+    /// Removes an obstacle, returning it if the id was in use.
+    pub fn remove_collider(&mut self, id: usize) -> Option<Collider> {
+        self.colliders.get_mut(id).and_then(Option::take)
+    }
+
+    pub fn collider(&self, id: usize) -> Option<&Collider> {
+        self.colliders.get(id).and_then(Option::as_ref)
+    }
+
+    pub fn collider_mut(&mut self, id: usize) -> Option<&mut Collider> {
+        self.colliders.get_mut(id).and_then(Option::as_mut)
+    }
+
+    /// Adds a dynamic body and returns the id used to look it up or remove it.
+    pub fn add_rigid_body(&mut self, body: RigidBody) -> usize {
+        self.rigid_bodies.push(Some(body));
+        self.rigid_bodies.len() - 1
+    }
+
+    pub fn remove_rigid_body(&mut self, id: usize) -> Option<RigidBody> {
+        self.rigid_bodies.get_mut(id).and_then(Option::take)
+    }
+
+    pub fn rigid_body(&self, id: usize) -> Option<&RigidBody> {
+        self.rigid_bodies.get(id).and_then(Option::as_ref)
+    }
+
+    /// Adds a particle source and returns the id used to change or remove it.
+    pub fn add_emitter(&mut self, emitter: Emitter) -> usize {
+        assert!(
+            emitter.material() < self.materials.len(),
+            "Unknown material id"
+        );
+        self.emitters.push(Some(emitter));
+        self.emitters.len() - 1
+    }
+
+    pub fn remove_emitter(&mut self, id: usize) -> Option<Emitter> {
+        self.emitters.get_mut(id).and_then(Option::take)
+    }
+
+    pub fn emitter_mut(&mut self, id: usize) -> Option<&mut Emitter> {
+        self.emitters.get_mut(id).and_then(Option::as_mut)
+    }
+
+    /// Adds a region that deletes particles, returning the id used to remove it.
+    pub fn add_sink(&mut self, sink: Sink) -> usize {
+        self.sinks.push(Some(sink));
+        self.sinks.len() - 1
+    }
+
+    pub fn remove_sink(&mut self, id: usize) -> Option<Sink> {
+        self.sinks.get_mut(id).and_then(Option::take)
+    }
+
+    /// Starts recording a frame every `every` steps, replacing any recording
+    /// in progress. See `Recorder` for what `quantized` does.
+    pub fn start_recording(&mut self, quantized: bool, every: u32) {
+        self.recorder = Some(Recorder::new(&self.domain, quantized, every));
+    }
+
+    /// Ends the recording in progress, returning its stream.
+    pub fn stop_recording(&mut self) -> Option<Vec<u8>> {
+        self.recorder.take().map(Recorder::finish)
+    }
+
+    pub fn recorder(&self) -> Option<&Recorder> {
+        self.recorder.as_ref()
+    }
+
+    pub fn pointer(&self) -> Option<&Pointer> {
+        self.pointer.as_ref()
+    }
+
+    /// Sets or clears the pointer acting on the material from the next step.
+    pub fn set_pointer(&mut self, pointer: Option<Pointer>) {
+        self.pointer = pointer;
+    }
+
+    pub fn params(&self) -> &SimParams {
+        &self.params
+    }
+
+    pub fn set_params(&mut self, params: SimParams) {
+        self.params = params;
+    }
+
+    /// The time step used by the most recent call to `step`.
+    pub fn last_dt(&self) -> f32 {
+        self.last_dt
+    }
+
+    /// Largest time step that satisfies the CFL condition for the current
+    /// state: no information may travel further than `cfl` cells per step,
+    /// whether carried by the material or by an elastic/pressure wave in it.
+    pub fn stable_dt(&self) -> f32 {
+        let dx = self.domain.cell_size();
+
+        let max_speed = self
+            .particles
+            .iter()
+            .map(|p| p.velocity.length() + self.materials[p.material].wave_speed(p))
+            .fold(0.0, f32::max);
+
+        if max_speed > 0.0 {
+            self.params.cfl() * dx / max_speed
+        } else {
+            f32::INFINITY
+        }
+    }
+
+    pub fn add_particle(&mut self, particle: Particle) {
+        assert!(
+            particle.material < self.materials.len(),
+            "Unknown material id"
+        );
+        self.particles.push(particle);
+    }
+
+    /// Scatters `num_particles` randomly over a 0.16-wide square around `center`.
+    pub fn add_particles(
+        &mut self,
+        num_particles: usize,
+        center: Vec2,
+        colour: u32,
+        material: usize,
+    ) {
+        assert!(material < self.materials.len(), "Unknown material id");
+
+        let range = Uniform::from(-1.0..=1.0);
+
+        (0..num_particles).for_each(|_| {
+            let pos = Vec2::new(range.sample(&mut self.rng), range.sample(&mut self.rng));
+            let pos = pos * 0.08 + center;
+
+            self.particles.push(Particle::new(pos, colour, material));
+        });
+    }
+
+    /// Fills `shape` with particles as described by `options`, returning how
+    /// many were added.
+    pub fn seed(&mut self, shape: &SeedShape, options: &SeedOptions) -> usize {
+        assert!(
+            options.material() < self.materials.len(),
+            "Unknown material id"
+        );
+
+        let particles = seeding::seed(shape, options, &mut self.rng);
+        let count = particles.len();
+        self.particles.extend(particles);
+        count
+    }
+
+    /// Turns the opaque pixels of `image` into particles described by
+    /// `options`, returning how many were added.
+    pub fn seed_image(&mut self, image: &SeedImage, options: &SeedOptions) -> usize {
+        assert!(
+            options.material() < self.materials.len(),
+            "Unknown material id"
+        );
+        assert!(image.is_valid(), "Image data does not match its size");
+
+        let particles = seeding::seed_image(image, options, &mut self.rng);
+        let count = particles.len();
+        self.particles.extend(particles);
+        count
+    }
+
+    /// Advances the simulation by `real_dt` wall-clock seconds, scaled by the
+    /// time scale, in fixed substeps. Time that does not fill a whole substep
+    /// carries over to the next call.
+    ///
+    /// A substep the CFL limit will not allow in one go is split into as
+    /// many shorter steps as it needs; `max_substeps` counts whole substeps.
+    pub fn update(&mut self, real_dt: f32) -> UpdateReport {
+        let substep_dt = self.params.substep_dt();
+        let mut report = UpdateReport::default();
+        if substep_dt <= 0.0 {
+            return report;
+        }
+
+        self.time_accumulator += real_dt.max(0.0) * self.params.time_scale();
+
+        while self.time_accumulator >= substep_dt {
+            if report.substeps >= self.params.max_substeps() {
+                // Keep the fractional part so the cadence stays even
+                let kept = self.time_accumulator % substep_dt;
+                report.dropped_time = self.time_accumulator - kept;
+                self.time_accumulator = kept;
+                break;
+            }
+
+            let mut remaining = substep_dt;
+            while remaining > 0.0 {
+                let dt = self.step(remaining);
+                if dt <= 0.0 {
+                    // Nothing can advance a scene whose limit has collapsed
+                    return report;
+                }
+                remaining -= dt;
+                report.steps += 1;
+            }
+
+            self.time_accumulator -= substep_dt;
+            report.simulated_time += substep_dt;
+            report.substeps += 1;
+        }
+
+        report
+    }
+
+    /// Advances the simulation by `max_dt`, or by less if the CFL condition
+    /// requires it. Returns the time step actually taken.
+    pub fn step(&mut self, max_dt: f32) -> f32 {
+        let dt = if self.params.cfl() > 0.0 {
+            max_dt.min(self.stable_dt())
+        } else {
+            max_dt
+        };
+        self.last_dt = dt;
+
+        self.grid.clear();
+        let dx = self.domain.cell_size();
+        let inv_dx = 1.0 / dx;
+
+        let kernel = self.params.kernel();
+
+        // Drop particles that have left the grid, through open walls or
+        // otherwise, or fallen into a sink
+        let domain = self.domain;
+        let nodes = IVec2::new(domain.grid_x() as i32, domain.grid_y() as i32) + IVec2::ONE;
+        let sinks = &self.sinks;
+        self.particles.retain(|p| {
+            kernel.stencil_lies_within(domain.to_grid(p.position), nodes)
+                && !sinks.iter().flatten().any(|sink| sink.contains(p.position))
+        });
+
+        // Particles to grid
+        for particle in self.particles.iter() {
+            let stencil = kernel.stencil(self.domain.to_grid(particle.position));
+            let material = &self.materials[particle.material];
+            let mass = material.particle_mass();
+
+            let p_f = material.kirchhoff_stress(particle);
+            let stress = -(dt * material.volume()) * p_f;
+            let affine = mass * particle.apic_affine_momentum;
+
+            // Translational momentum
+            for node in stencil.nodes(inv_dx) {
+                let dpos = node.dpos * dx;
+
+                let cell = &mut self.grid[node.coord];
+
+                cell.velocity += (particle.velocity * mass + affine * dpos) * node.weight
+                    + stress * node.mls_gradient;
+                cell.mass += mass * node.weight;
+            }
+        }
+
+        // For all grid nodes
+        for (coord, cell) in self.grid.iter_mut() {
+            if cell.mass <= 0.0 {
+                continue;
+            }
+
+            // Normalise by mass, keeping the mass for rigid body coupling
+            cell.velocity /= cell.mass;
+
+            // Gravity
+            cell.velocity += self.params.gravity() * dt;
+
+            let position = self.domain.node_position(coord);
+
+            // User interaction
+            if let Some(pointer) = &self.pointer {
+                cell.velocity = pointer.apply(position, cell.velocity, dt);
+            }
+
+            // Obstacles
+            for collider in self.colliders.iter().flatten() {
+                cell.velocity = collider.project(position, cell.velocity);
+            }
+            for body in self.rigid_bodies.iter_mut().flatten() {
+                cell.velocity = body.couple(position, cell.mass, cell.velocity);
+            }
+
+            // Distance from the node to each wall
+            let boundary = self.params.boundary_thickness();
+            let low = position - self.domain.origin();
+            let high = self.domain.size() - low;
+
+            for wall in Wall::ALL {
+                let distance = match wall {
+                    Wall::Left => low.x,
+                    Wall::Right => high.x,
+                    Wall::Bottom => low.y,
+                    Wall::Top => high.y,
+                };
+                if distance < boundary {
+                    cell.velocity = self
+                        .params
+                        .boundary(wall)
+                        .project(cell.velocity, wall.normal());
+                }
+            }
+        }
+
+        // Rigid bodies react to what the grid pushed on them
+        for body in self.rigid_bodies.iter_mut().flatten() {
+            body.integrate_velocity(self.params.gravity(), dt);
+        }
+
+        // Grid to particles
+        for particle in self.particles.iter_mut() {
+            let stencil = kernel.stencil(self.domain.to_grid(particle.position));
+
+            particle.apic_affine_momentum = Mat2::ZERO;
+            particle.velocity = Vec2::ZERO;
+
+            for node in stencil.nodes(inv_dx) {
+                let grid_v = self.grid[node.coord].velocity;
+
+                // Velocity
+                particle.velocity += node.weight * grid_v;
+                // APIC C
+                particle.apic_affine_momentum += outer_product(grid_v, node.mls_gradient);
+            }
+
+            // Advection
+            particle.position += dt * particle.velocity;
+
+            // MLS-MPM F-update
+            let trial_f = (Mat2::IDENTITY + particle.apic_affine_momentum * dt)
+                * particle.deformation_gradient;
+
+            self.materials[particle.material].update_deformation_gradient(particle, trial_f);
+        }
+
+        // Kinematic colliders
+        for collider in self.colliders.iter_mut().flatten() {
+            collider.advance(dt);
+        }
+
+        if let Some(pointer) = &mut self.pointer {
+            pointer.advance(dt);
+        }
+
+        // New particles join from the next step, so its time step accounts
+        // for their speed
+        for emitter in self.emitters.iter_mut().flatten() {
+            emitter.emit(dt, &mut self.particles);
+        }
+
+        if let Some(recorder) = &mut self.recorder {
+            recorder.record(dt, &self.particles);
+        }
+
+        // Rigid bodies, which the domain walls keep inside
+        for body in self.rigid_bodies.iter_mut().flatten() {
+            body.integrate_position(dt);
+
+            let low = body.collider().position() - self.domain.origin();
+            let high = self.domain.size() - low;
+            let boundary = self.params.boundary_thickness();
+
+            for wall in Wall::ALL {
+                if self.params.boundary(wall).kind() == BoundaryType::Open {
+                    continue;
+                }
+                let distance = match wall {
+                    Wall::Left => low.x,
+                    Wall::Right => high.x,
+                    Wall::Bottom => low.y,
+                    Wall::Top => high.y,
+                };
+                body.resolve_wall(wall.normal(), distance - boundary);
+            }
+        }
+
+        dt
+    }
+}
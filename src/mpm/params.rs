@@ -0,0 +1,161 @@
+use super::{Boundary, BoundaryType, Kernel, Vec2, Wall};
+use wasm_bindgen::prelude::*;
+
+/// Scene-wide settings that are not tied to a material.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, serde::Serialize, serde::Deserialize)]
+pub struct SimParams {
+    gravity: Vec2,
+    boundary_thickness: f32,
+    /// Indexed by `Wall`
+    walls: [Boundary; 4],
+    kernel: Kernel,
+    cfl: f32,
+    substep_dt: f32,
+    max_substeps: u32,
+    time_scale: f32,
+}
+
+#[wasm_bindgen]
+impl SimParams {
+    pub fn new(gravity_x: f32, gravity_y: f32, boundary_thickness: f32) -> Self {
+        Self {
+            gravity: Vec2::new(gravity_x, gravity_y),
+            boundary_thickness,
+            walls: [
+                Boundary::new(BoundaryType::Sticky, 0.0),
+                Boundary::new(BoundaryType::Sticky, 0.0),
+                Boundary::new(BoundaryType::Separate, 0.0),
+                Boundary::new(BoundaryType::Sticky, 0.0),
+            ],
+            kernel: Kernel::Quadratic,
+            cfl: 0.5,
+            substep_dt: 1e-4,
+            max_substeps: 20,
+            time_scale: 1.0,
+        }
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn gravity_x(&self) -> f32 {
+        self.gravity.x
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_gravity_x(&mut self, gravity_x: f32) {
+        self.gravity.x = gravity_x;
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn gravity_y(&self) -> f32 {
+        self.gravity.y
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_gravity_y(&mut self, gravity_y: f32) {
+        self.gravity.y = gravity_y;
+    }
+
+    /// Width of the wall region, in world units
+    #[wasm_bindgen(getter)]
+    pub fn boundary_thickness(&self) -> f32 {
+        self.boundary_thickness
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_boundary_thickness(&mut self, boundary_thickness: f32) {
+        self.boundary_thickness = boundary_thickness;
+    }
+
+    /// Coulomb friction coefficient between the material and the floor,
+    /// which is the bottom wall's boundary friction
+    #[wasm_bindgen(getter)]
+    pub fn floor_friction(&self) -> f32 {
+        self.boundary(Wall::Bottom).friction()
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_floor_friction(&mut self, floor_friction: f32) {
+        self.walls[Wall::Bottom as usize].set_friction(floor_friction);
+    }
+
+    /// Condition applied at one side of the domain
+    pub fn boundary(&self, wall: Wall) -> Boundary {
+        self.walls[wall as usize]
+    }
+
+    pub fn set_boundary(&mut self, wall: Wall, boundary: Boundary) {
+        self.walls[wall as usize] = boundary;
+    }
+
+    /// Interpolation kernel for particle/grid transfers
+    #[wasm_bindgen(getter)]
+    pub fn kernel(&self) -> Kernel {
+        self.kernel
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_kernel(&mut self, kernel: Kernel) {
+        self.kernel = kernel;
+    }
+
+    /// Fraction of a grid cell that anything may travel in one step.
+    /// `MpmSolver::step` shortens its time steps to respect it, and zero
+    /// disables the limit.
+    #[wasm_bindgen(getter)]
+    pub fn cfl(&self) -> f32 {
+        self.cfl
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_cfl(&mut self, cfl: f32) {
+        self.cfl = cfl;
+    }
+
+    /// Time step used by `MpmSolver::update`, in simulated seconds. Substeps
+    /// the CFL limit cannot take in one go are split into shorter steps.
+    #[wasm_bindgen(getter)]
+    pub fn substep_dt(&self) -> f32 {
+        self.substep_dt
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_substep_dt(&mut self, substep_dt: f32) {
+        self.substep_dt = substep_dt;
+    }
+
+    /// Most steps `MpmSolver::update` may take in one call. Any time beyond
+    /// that is dropped, so a slow frame cannot snowball into slower ones.
+    #[wasm_bindgen(getter)]
+    pub fn max_substeps(&self) -> u32 {
+        self.max_substeps
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_max_substeps(&mut self, max_substeps: u32) {
+        self.max_substeps = max_substeps;
+    }
+
+    /// Simulated seconds per wall-clock second
+    #[wasm_bindgen(getter)]
+    pub fn time_scale(&self) -> f32 {
+        self.time_scale
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_time_scale(&mut self, time_scale: f32) {
+        self.time_scale = time_scale;
+    }
+}
+
+impl SimParams {
+    pub fn gravity(&self) -> Vec2 {
+        self.gravity
+    }
+}
+
+impl Default for SimParams {
+    fn default() -> Self {
+        Self::new(0.0, -200.0, 0.05)
+    }
+}
@@ -0,0 +1,121 @@
+use wasm_bindgen::prelude::*;
+
+use super::Vec2;
+
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PointerMode {
+    /// Accelerates material away from the pointer
+    Push,
+    /// Accelerates material towards the pointer
+    Pull,
+    /// Makes material move with the pointer
+    Grab,
+}
+
+/// A disc of influence, usually under the mouse or a finger, applied during
+/// the grid update. Influence falls off linearly to zero at the radius.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug)]
+pub struct Pointer {
+    position: Vec2,
+    velocity: Vec2,
+    radius: f32,
+    mode: PointerMode,
+    /// Acceleration at the center for push and pull
+    strength: f32,
+    /// How much longer the pointer may glide along its velocity
+    glide_time: f32,
+}
+
+#[wasm_bindgen]
+impl Pointer {
+    pub fn new(x: f32, y: f32, radius: f32, mode: PointerMode, strength: f32) -> Self {
+        Self {
+            position: Vec2::new(x, y),
+            velocity: Vec2::ZERO,
+            radius,
+            mode,
+            strength,
+            glide_time: 0.0,
+        }
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn x(&self) -> f32 {
+        self.position.x
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn y(&self) -> f32 {
+        self.position.y
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn radius(&self) -> f32 {
+        self.radius
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn mode(&self) -> PointerMode {
+        self.mode
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn strength(&self) -> f32 {
+        self.strength
+    }
+
+    /// How fast the pointer is moving, which grabbed material follows, and
+    /// for how many simulated seconds it keeps moving without a new event.
+    /// Usually that is the time between the last two events.
+    pub fn set_velocity(&mut self, velocity_x: f32, velocity_y: f32, glide_time: f32) {
+        self.velocity = Vec2::new(velocity_x, velocity_y);
+        self.glide_time = glide_time.max(0.0);
+    }
+}
+
+impl Pointer {
+    pub fn position(&self) -> Vec2 {
+        self.position
+    }
+
+    pub fn velocity(&self) -> Vec2 {
+        self.velocity
+    }
+
+    pub fn glide_time(&self) -> f32 {
+        self.glide_time
+    }
+
+    /// Moves the pointer along its velocity, so it glides between updates.
+    /// Once its glide time runs out it stops, holding grabbed material still
+    /// until the next event.
+    pub fn advance(&mut self, dt: f32) {
+        let glide = dt.min(self.glide_time);
+        self.position += glide * self.velocity;
+        self.glide_time -= glide;
+
+        if self.glide_time <= 0.0 {
+            self.velocity = Vec2::ZERO;
+        }
+    }
+
+    /// Applies the pointer to a grid velocity at `position`.
+    pub fn apply(&self, position: Vec2, velocity: Vec2, dt: f32) -> Vec2 {
+        let offset = position - self.position;
+        let distance = offset.length();
+        if distance >= self.radius {
+            return velocity;
+        }
+
+        let falloff = 1.0 - distance / self.radius;
+        let outward = offset.normalize_or_zero();
+
+        match self.mode {
+            PointerMode::Push => velocity + dt * self.strength * falloff * outward,
+            PointerMode::Pull => velocity - dt * self.strength * falloff * outward,
+            PointerMode::Grab => velocity + falloff * (self.velocity - velocity),
+        }
+    }
+}
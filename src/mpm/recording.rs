@@ -0,0 +1,490 @@
+use super::{Domain, Particle, Vec2};
+use std::convert::TryFrom;
+
+/// Identifies a recorded particle stream
+const MAGIC: &[u8; 4] = b"MPMR";
+const VERSION: u32 = 1;
+
+/// Longest run of delta frames between keyframes, which bounds the work a
+/// seek has to do
+const KEYFRAME_INTERVAL: u32 = 60;
+
+/// Frame flags
+const KEYFRAME: u8 = 1;
+const HAS_COLOURS: u8 = 2;
+
+/// Captures particle positions and colours as the solver steps, into a
+/// compact stream that `Playback` can show without simulating.
+///
+/// Unquantized frames store positions as raw `f32`s. Quantized frames store
+/// them as 16-bit fractions of the domain, and frames between keyframes store
+/// only the change from the previous frame as variable-length integers,
+/// which is usually one byte per coordinate. Colours are only stored when
+/// they change.
+pub struct Recorder {
+    bytes: Vec<u8>,
+    quantizer: Option<Quantizer>,
+    /// Record one frame in this many steps
+    every: u32,
+    steps_since_frame: u32,
+    /// Simulated time since the last recorded frame
+    pending_time: f32,
+    frames_since_keyframe: u32,
+    frame_count: u32,
+    previous: Vec<[u16; 2]>,
+    previous_colours: Vec<u32>,
+}
+
+impl Recorder {
+    pub fn new(domain: &Domain, quantized: bool, every: u32) -> Self {
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend_from_slice(&VERSION.to_le_bytes());
+        bytes.push(quantized as u8);
+
+        let min = domain.origin();
+        let max = min + domain.size();
+        for v in [min.x, min.y, max.x, max.y] {
+            bytes.extend_from_slice(&v.to_le_bytes());
+        }
+
+        Self {
+            bytes,
+            quantizer: quantized.then_some(Quantizer { min, max }),
+            every: every.max(1),
+            steps_since_frame: 0,
+            pending_time: 0.0,
+            frames_since_keyframe: 0,
+            frame_count: 0,
+            previous: vec![],
+            previous_colours: vec![],
+        }
+    }
+
+    pub fn frame_count(&self) -> u32 {
+        self.frame_count
+    }
+
+    /// Notes a step of `dt` that left the particles as given, recording a
+    /// frame if one is due.
+    pub fn record(&mut self, dt: f32, particles: &[Particle]) {
+        self.pending_time += dt;
+        self.steps_since_frame += 1;
+        if self.steps_since_frame < self.every {
+            return;
+        }
+
+        let count_changed = particles.len() != self.previous_colours.len();
+        let keyframe = self.frame_count == 0
+            || count_changed
+            || self.quantizer.is_none()
+            || self.frames_since_keyframe >= KEYFRAME_INTERVAL;
+        let colours_changed = self.frame_count == 0
+            || count_changed
+            || particles
+                .iter()
+                .zip(&self.previous_colours)
+                .any(|(p, c)| p.colour != *c);
+
+        let flags =
+            if keyframe { KEYFRAME } else { 0 } | if colours_changed { HAS_COLOURS } else { 0 };
+
+        self.bytes
+            .extend_from_slice(&self.pending_time.to_le_bytes());
+        self.bytes.push(flags);
+        write_varint(&mut self.bytes, particles.len() as u64);
+
+        match &self.quantizer {
+            None => {
+                for p in particles {
+                    self.bytes.extend_from_slice(&p.position.x.to_le_bytes());
+                    self.bytes.extend_from_slice(&p.position.y.to_le_bytes());
+                }
+            }
+            Some(quantizer) => {
+                let current: Vec<[u16; 2]> = particles
+                    .iter()
+                    .map(|p| quantizer.quantize(p.position))
+                    .collect();
+
+                if keyframe {
+                    for q in &current {
+                        self.bytes.extend_from_slice(&q[0].to_le_bytes());
+                        self.bytes.extend_from_slice(&q[1].to_le_bytes());
+                    }
+                } else {
+                    for (q, prev) in current.iter().zip(&self.previous) {
+                        for axis in 0..2 {
+                            let delta = q[axis] as i64 - prev[axis] as i64;
+                            write_varint(&mut self.bytes, zigzag(delta));
+                        }
+                    }
+                }
+
+                self.previous = current;
+            }
+        }
+
+        if colours_changed {
+            self.previous_colours.clear();
+            for p in particles {
+                self.bytes.extend_from_slice(&p.colour.to_le_bytes());
+                self.previous_colours.push(p.colour);
+            }
+        }
+
+        self.frames_since_keyframe = if keyframe {
+            0
+        } else {
+            self.frames_since_keyframe + 1
+        };
+        self.frame_count += 1;
+        self.steps_since_frame = 0;
+        self.pending_time = 0.0;
+    }
+
+    /// The recorded stream.
+    pub fn finish(self) -> Vec<u8> {
+        self.bytes
+    }
+}
+
+/// Where each frame of a recording starts, found once when it is loaded
+struct FrameIndex {
+    /// Offset of the frame's position data
+    offset: usize,
+    keyframe: bool,
+    count: usize,
+    /// Simulated time between the previous frame and this one
+    time: f32,
+    /// Offset of the colour data this frame uses, which may belong to an
+    /// earlier frame
+    colours_offset: usize,
+}
+
+/// Plays back a stream made by `Recorder`, frame by frame.
+pub struct Playback {
+    bytes: Vec<u8>,
+    /// Corners of the recorded domain
+    bounds: (Vec2, Vec2),
+    quantizer: Option<Quantizer>,
+    frames: Vec<FrameIndex>,
+    frame: usize,
+    /// Simulated time played since the current frame was shown
+    time_into_frame: f32,
+    quantized: Vec<[u16; 2]>,
+    positions: Vec<Vec2>,
+    colours: Vec<u32>,
+}
+
+impl Playback {
+    pub fn new(bytes: Vec<u8>) -> Result<Self, String> {
+        let mut reader = Reader::new(&bytes);
+
+        if reader.take(4)? != MAGIC {
+            return Err("Not an MPM recording".into());
+        }
+        let version = reader.u32()?;
+        if version != VERSION {
+            return Err(format!(
+                "MPM recording has version {}, expected {}",
+                version, VERSION
+            ));
+        }
+
+        let quantized = reader.u8()? != 0;
+        let min = Vec2::new(reader.f32()?, reader.f32()?);
+        let max = Vec2::new(reader.f32()?, reader.f32()?);
+        let quantizer = quantized.then_some(Quantizer { min, max });
+
+        // Walk the frames once to find where each starts, checking that every
+        // frame has what decoding it will read
+        let mut frames = vec![];
+        let mut keyframe_count = None;
+        let mut colours = None;
+        while !reader.is_empty() {
+            let time = reader.f32()?;
+            let flags = reader.u8()?;
+            let count = usize::try_from(reader.varint()?)
+                .map_err(|_| "MPM recording has too many particles")?;
+            let offset = reader.position;
+            let keyframe = flags & KEYFRAME != 0;
+
+            if frames.is_empty() && !keyframe {
+                return Err("MPM recording does not start with a keyframe".into());
+            }
+
+            if keyframe {
+                keyframe_count = Some(count);
+            } else if keyframe_count != Some(count) {
+                // Deltas apply to the positions of the keyframe before them
+                return Err(
+                    "MPM recording has a delta frame that does not match its keyframe".into(),
+                );
+            }
+
+            match (quantized, keyframe) {
+                (false, _) => {
+                    reader.take_array(count, 8)?;
+                }
+                (true, true) => {
+                    reader.take_array(count, 4)?;
+                }
+                (true, false) => {
+                    for _ in 0..count {
+                        reader.varint()?;
+                        reader.varint()?;
+                    }
+                }
+            }
+
+            if flags & HAS_COLOURS != 0 {
+                colours = Some((reader.position, count));
+                reader.take_array(count, 4)?;
+            }
+
+            let colours_offset = match colours {
+                Some((offset, colours_count)) if colours_count == count => offset,
+                Some(_) => {
+                    return Err("MPM recording reuses colours of a different particle count".into())
+                }
+                None => return Err("MPM recording has no colours".into()),
+            };
+
+            frames.push(FrameIndex {
+                offset,
+                keyframe,
+                count,
+                time,
+                colours_offset,
+            });
+        }
+
+        if frames.is_empty() {
+            return Err("MPM recording has no frames".into());
+        }
+
+        let mut playback = Self {
+            bytes,
+            bounds: (min, max),
+            quantizer,
+            frames,
+            frame: 0,
+            time_into_frame: 0.0,
+            quantized: vec![],
+            positions: vec![],
+            colours: vec![],
+        };
+        playback.seek(0);
+        Ok(playback)
+    }
+
+    pub fn frame_count(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Bottom-left and top-right corners of the domain that was recorded
+    pub fn bounds(&self) -> (Vec2, Vec2) {
+        self.bounds
+    }
+
+    /// Index of the frame being shown
+    pub fn frame(&self) -> usize {
+        self.frame
+    }
+
+    pub fn positions(&self) -> &[Vec2] {
+        &self.positions
+    }
+
+    pub fn colours(&self) -> &[u32] {
+        &self.colours
+    }
+
+    /// Whether the last frame is being shown
+    pub fn finished(&self) -> bool {
+        self.frame + 1 == self.frames.len()
+    }
+
+    /// Shows frame `frame`, or the last one if it is past the end.
+    pub fn seek(&mut self, frame: usize) {
+        let frame = frame.min(self.frames.len() - 1);
+
+        // Delta frames need everything since the keyframe before them
+        let start = if frame > self.frame && frame - self.frame <= KEYFRAME_INTERVAL as usize {
+            self.frame + 1
+        } else {
+            (0..=frame)
+                .rev()
+                .find(|&i| self.frames[i].keyframe)
+                .unwrap_or(0)
+        };
+
+        for i in start..=frame {
+            self.decode(i);
+        }
+        self.frame = frame;
+        self.time_into_frame = 0.0;
+    }
+
+    /// Plays `time` simulated seconds, moving on as many frames as the
+    /// recorded step times add up to.
+    pub fn advance(&mut self, time: f32) {
+        self.time_into_frame += time;
+
+        let mut frame = self.frame;
+        while frame + 1 < self.frames.len() && self.time_into_frame >= self.frames[frame + 1].time {
+            self.time_into_frame -= self.frames[frame + 1].time;
+            frame += 1;
+        }
+
+        if frame != self.frame {
+            let time_into_frame = self.time_into_frame;
+            self.seek(frame);
+            self.time_into_frame = time_into_frame;
+        }
+    }
+
+    /// Decodes frame `index` on top of the previous frame's positions. The
+    /// stream was validated when indexed, so reads cannot run out.
+    fn decode(&mut self, index: usize) {
+        let info = &self.frames[index];
+        let mut reader = Reader::new(&self.bytes);
+        reader.position = info.offset;
+
+        self.positions.clear();
+        match &self.quantizer {
+            None => {
+                for _ in 0..info.count {
+                    let x = reader.f32().unwrap();
+                    let y = reader.f32().unwrap();
+                    self.positions.push(Vec2::new(x, y));
+                }
+            }
+            Some(quantizer) => {
+                if info.keyframe {
+                    self.quantized.clear();
+                    for _ in 0..info.count {
+                        let x = reader.u16().unwrap();
+                        let y = reader.u16().unwrap();
+                        self.quantized.push([x, y]);
+                    }
+                } else {
+                    for q in self.quantized.iter_mut() {
+                        for axis in q.iter_mut() {
+                            let delta = unzigzag(reader.varint().unwrap());
+                            *axis = (*axis as i64 + delta) as u16;
+                        }
+                    }
+                }
+                self.positions
+                    .extend(self.quantized.iter().map(|&q| quantizer.dequantize(q)));
+            }
+        }
+
+        reader.position = info.colours_offset;
+        self.colours.clear();
+        for _ in 0..info.count {
+            self.colours.push(reader.u32().unwrap());
+        }
+    }
+}
+
+/// Maps positions in the domain onto the full range of a `u16`
+struct Quantizer {
+    min: Vec2,
+    max: Vec2,
+}
+
+impl Quantizer {
+    fn quantize(&self, position: Vec2) -> [u16; 2] {
+        let unit = ((position - self.min) / (self.max - self.min)).clamp(Vec2::ZERO, Vec2::ONE);
+        let q = (unit * u16::MAX as f32).round();
+        [q.x as u16, q.y as u16]
+    }
+
+    fn dequantize(&self, q: [u16; 2]) -> Vec2 {
+        let unit = Vec2::new(q[0] as f32, q[1] as f32) / u16::MAX as f32;
+        self.min + unit * (self.max - self.min)
+    }
+}
+
+/// Maps signed integers to unsigned ones with small magnitudes staying small
+fn zigzag(v: i64) -> u64 {
+    ((v << 1) ^ (v >> 63)) as u64
+}
+
+fn unzigzag(v: u64) -> i64 {
+    (v >> 1) as i64 ^ -((v & 1) as i64)
+}
+
+/// LEB128: seven bits per byte, high bit set on all but the last
+fn write_varint(bytes: &mut Vec<u8>, mut v: u64) {
+    while v >= 0x80 {
+        bytes.push(v as u8 | 0x80);
+        v >>= 7;
+    }
+    bytes.push(v as u8);
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, position: 0 }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.position >= self.bytes.len()
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        let end = match self.position.checked_add(len) {
+            Some(end) if end <= self.bytes.len() => end,
+            _ => return Err("MPM recording is truncated".into()),
+        };
+        let slice = &self.bytes[self.position..end];
+        self.position = end;
+        Ok(slice)
+    }
+
+    /// Takes `count` items of `size` bytes each.
+    fn take_array(&mut self, count: usize, size: usize) -> Result<&'a [u8], String> {
+        let len = count
+            .checked_mul(size)
+            .ok_or("MPM recording is truncated")?;
+        self.take(len)
+    }
+
+    fn u8(&mut self) -> Result<u8, String> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, String> {
+        let b = self.take(2)?;
+        Ok(u16::from_le_bytes([b[0], b[1]]))
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        let b = self.take(4)?;
+        Ok(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    fn f32(&mut self) -> Result<f32, String> {
+        Ok(f32::from_bits(self.u32()?))
+    }
+
+    fn varint(&mut self) -> Result<u64, String> {
+        let mut v = 0;
+        for shift in (0..64).step_by(7) {
+            let byte = self.u8()?;
+            v |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(v);
+            }
+        }
+        Err("MPM recording has a malformed integer".into())
+    }
+}
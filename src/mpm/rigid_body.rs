@@ -0,0 +1,149 @@
+use wasm_bindgen::prelude::*;
+
+use super::{Collider, Vec2};
+
+/// A collider that moves in response to the material around it.
+///
+/// During the grid update the body constrains material like a kinematic
+/// collider, and takes the opposite of the momentum it removed, so material
+/// and body push on each other. Masses are in the same units as particle
+/// masses, so a body floats when its density is below the particle mass per
+/// unit area of the fluid around it. An infinite mass or inertia makes the
+/// body immovable in translation or rotation.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, serde::Serialize, serde::Deserialize)]
+pub struct RigidBody {
+    collider: Collider,
+    mass: f32,
+    inertia: f32,
+    /// Momentum received from the grid during the current step
+    impulse: Vec2,
+    angular_impulse: f32,
+}
+
+#[wasm_bindgen]
+impl RigidBody {
+    /// A body with the shape, pose, initial velocity and surface behaviour of
+    /// `collider`, and the given mass and moment of inertia. Both must be
+    /// positive.
+    pub fn new(collider: Collider, mass: f32, inertia: f32) -> Result<RigidBody, String> {
+        if mass.is_nan() || mass <= 0.0 {
+            return Err("Rigid body mass must be positive".into());
+        }
+        if inertia.is_nan() || inertia <= 0.0 {
+            return Err("Rigid body inertia must be positive".into());
+        }
+
+        Ok(Self {
+            collider,
+            mass,
+            inertia,
+            impulse: Vec2::ZERO,
+            angular_impulse: 0.0,
+        })
+    }
+
+    /// A body of uniform density, in mass per unit area.
+    pub fn from_density(collider: Collider, density: f32) -> Result<RigidBody, String> {
+        let (mass, inertia) = collider.mass_properties(density);
+        Self::new(collider, mass, inertia)
+    }
+
+    /// The body's current shape, pose and velocity
+    #[wasm_bindgen(getter)]
+    pub fn collider(&self) -> Collider {
+        self.collider
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn mass(&self) -> f32 {
+        self.mass
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn inertia(&self) -> f32 {
+        self.inertia
+    }
+}
+
+impl RigidBody {
+    /// Zero for an infinitely heavy body
+    fn inv_mass(&self) -> f32 {
+        self.mass.recip()
+    }
+
+    fn inv_inertia(&self) -> f32 {
+        self.inertia.recip()
+    }
+
+    /// Constrains a grid node's velocity like a collider would, and records
+    /// the reaction on the body.
+    pub(crate) fn couple(&mut self, position: Vec2, node_mass: f32, velocity: Vec2) -> Vec2 {
+        let constrained = self.collider.project(position, velocity);
+
+        let reaction = node_mass * (velocity - constrained);
+        self.impulse += reaction;
+        self.angular_impulse += (position - self.collider.position()).perp_dot(reaction);
+
+        constrained
+    }
+
+    /// Applies the momentum gathered from the grid and gravity to the
+    /// body's velocity. Immovable bodies keep the velocity they were given.
+    pub(crate) fn integrate_velocity(&mut self, gravity: Vec2, dt: f32) {
+        let mut velocity = self.collider.velocity();
+        let mut angular_velocity = self.collider.angular_velocity();
+
+        if self.inv_mass() > 0.0 {
+            velocity += self.impulse * self.inv_mass() + gravity * dt;
+        }
+        if self.inv_inertia() > 0.0 {
+            angular_velocity += self.angular_impulse * self.inv_inertia();
+        }
+        self.collider
+            .set_velocity(velocity.x, velocity.y, angular_velocity);
+
+        self.impulse = Vec2::ZERO;
+        self.angular_impulse = 0.0;
+    }
+
+    /// Moves the body along its velocity.
+    pub(crate) fn integrate_position(&mut self, dt: f32) {
+        self.collider.advance(dt);
+    }
+
+    /// Pushes the body out of a wall `distance` from its position along the
+    /// wall's inward `normal`, and stops the contact point moving into it.
+    pub(crate) fn resolve_wall(&mut self, normal: Vec2, distance: f32) {
+        if self.inv_mass() == 0.0 {
+            return;
+        }
+
+        let position = self.collider.position();
+        let contact = self.collider.support_point(-normal);
+
+        let penetration = (position - contact).dot(normal) - distance;
+        if penetration <= 0.0 || !penetration.is_finite() {
+            return;
+        }
+        self.collider.translate(penetration * normal);
+
+        // Inelastic contact impulse at the deepest point
+        let arm = contact - position;
+        let velocity = self.collider.velocity_at(contact);
+        let normal_speed = velocity.dot(normal);
+        if normal_speed >= 0.0 {
+            return;
+        }
+
+        let arm_normal = arm.perp_dot(normal);
+        let impulse =
+            -normal_speed / (self.inv_mass() + arm_normal * arm_normal * self.inv_inertia());
+
+        let velocity = self.collider.velocity() + impulse * normal * self.inv_mass();
+        let angular_velocity =
+            self.collider.angular_velocity() + impulse * arm_normal * self.inv_inertia();
+        self.collider
+            .set_velocity(velocity.x, velocity.y, angular_velocity);
+    }
+}
@@ -0,0 +1,409 @@
+use rand::Rng;
+use wasm_bindgen::prelude::*;
+
+use super::{Particle, Vec2};
+
+/// Most particles one call may seed, however dense the options. Keeps a
+/// stray density from exhausting memory
+const MAX_SEEDED: usize = 1 << 20;
+
+/// How particle positions are chosen inside a shape.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Sampling {
+    /// Independent uniform points. Clumpy, with visible gaps
+    Random,
+    /// One uniform point in each cell of a grid at the particle spacing.
+    /// Even coverage without the artefacts of a regular lattice
+    Jittered,
+}
+
+#[derive(Clone, Debug)]
+enum Shape {
+    Rectangle {
+        min: Vec2,
+        max: Vec2,
+    },
+    Disk {
+        center: Vec2,
+        radius: f32,
+    },
+    Ring {
+        center: Vec2,
+        inner: f32,
+        outer: f32,
+    },
+    Polygon {
+        vertices: Vec<Vec2>,
+    },
+}
+
+/// A region of world space to fill with particles.
+#[wasm_bindgen]
+#[derive(Clone, Debug)]
+pub struct SeedShape {
+    shape: Shape,
+}
+
+#[wasm_bindgen]
+impl SeedShape {
+    /// Axis-aligned box between two corners
+    pub fn rectangle(min_x: f32, min_y: f32, max_x: f32, max_y: f32) -> Self {
+        Self {
+            shape: Shape::Rectangle {
+                min: Vec2::new(min_x, min_y),
+                max: Vec2::new(max_x, max_y),
+            },
+        }
+    }
+
+    pub fn disk(x: f32, y: f32, radius: f32) -> Self {
+        Self {
+            shape: Shape::Disk {
+                center: Vec2::new(x, y),
+                radius,
+            },
+        }
+    }
+
+    /// The area between two circles
+    pub fn ring(x: f32, y: f32, inner_radius: f32, outer_radius: f32) -> Self {
+        Self {
+            shape: Shape::Ring {
+                center: Vec2::new(x, y),
+                inner: inner_radius,
+                outer: outer_radius,
+            },
+        }
+    }
+
+    /// Simple polygon from flattened `[x0, y0, x1, y1, ...]` vertices, in
+    /// either winding order
+    pub fn polygon(vertices: &[f32]) -> Self {
+        Self {
+            shape: Shape::Polygon {
+                vertices: vertices
+                    .chunks_exact(2)
+                    .map(|v| Vec2::new(v[0], v[1]))
+                    .collect(),
+            },
+        }
+    }
+}
+
+impl SeedShape {
+    pub fn contains(&self, position: Vec2) -> bool {
+        match &self.shape {
+            Shape::Rectangle { min, max } => {
+                position.cmpge(*min).all() && position.cmple(*max).all()
+            }
+            Shape::Disk { center, radius } => position.distance(*center) <= *radius,
+            Shape::Ring {
+                center,
+                inner,
+                outer,
+            } => {
+                let distance = position.distance(*center);
+                distance >= *inner && distance <= *outer
+            }
+            Shape::Polygon { vertices } => {
+                // Even-odd rule: count edges crossed by a ray towards +x
+                let mut inside = false;
+                for (i, a) in vertices.iter().enumerate() {
+                    let b = vertices[(i + 1) % vertices.len()];
+                    if (a.y > position.y) != (b.y > position.y) {
+                        let t = (position.y - a.y) / (b.y - a.y);
+                        if position.x < a.x + t * (b.x - a.x) {
+                            inside = !inside;
+                        }
+                    }
+                }
+                inside
+            }
+        }
+    }
+
+    pub fn area(&self) -> f32 {
+        use std::f32::consts::PI;
+
+        match &self.shape {
+            Shape::Rectangle { min, max } => {
+                let size = (*max - *min).max(Vec2::ZERO);
+                size.x * size.y
+            }
+            Shape::Disk { radius, .. } => PI * radius * radius,
+            Shape::Ring { inner, outer, .. } => PI * (outer * outer - inner * inner).max(0.0),
+            Shape::Polygon { vertices } => {
+                // Shoelace formula
+                let twice_signed: f32 = vertices
+                    .iter()
+                    .enumerate()
+                    .map(|(i, a)| a.perp_dot(vertices[(i + 1) % vertices.len()]))
+                    .sum();
+                0.5 * twice_signed.abs()
+            }
+        }
+    }
+
+    /// Corners of the smallest axis-aligned box containing the shape.
+    pub fn bounds(&self) -> (Vec2, Vec2) {
+        match &self.shape {
+            Shape::Rectangle { min, max } => (*min, *max),
+            Shape::Disk { center, radius } => (*center - *radius, *center + *radius),
+            Shape::Ring { center, outer, .. } => (*center - *outer, *center + *outer),
+            Shape::Polygon { vertices } => vertices.iter().fold(
+                (Vec2::splat(f32::INFINITY), Vec2::splat(f32::NEG_INFINITY)),
+                |(min, max), v| (min.min(*v), max.max(*v)),
+            ),
+        }
+    }
+}
+
+/// An RGBA bitmap to turn into particles, such as a logo or drawing.
+/// Pixels at least as opaque as the threshold become material, coloured
+/// like the pixel.
+#[wasm_bindgen]
+#[derive(Clone, Debug)]
+pub struct SeedImage {
+    /// Rows from the top, as from `ImageData.data`
+    pixels: Vec<u8>,
+    width: usize,
+    height: usize,
+    /// World position of the image's bottom-left corner
+    origin: Vec2,
+    /// Width of the whole image in world units
+    world_width: f32,
+    alpha_threshold: u8,
+}
+
+#[wasm_bindgen]
+impl SeedImage {
+    /// Wraps `width * height` RGBA pixels, placed over the unit square's
+    /// width until moved with `place`.
+    pub fn new(pixels: Vec<u8>, width: usize, height: usize) -> Self {
+        Self {
+            pixels,
+            width,
+            height,
+            origin: Vec2::ZERO,
+            world_width: 1.0,
+            alpha_threshold: 128,
+        }
+    }
+
+    /// Puts the bottom-left corner at `(x, y)` and scales the image to
+    /// `world_width`, keeping its aspect ratio.
+    pub fn place(&mut self, x: f32, y: f32, world_width: f32) {
+        self.origin = Vec2::new(x, y);
+        self.world_width = world_width;
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn alpha_threshold(&self) -> u8 {
+        self.alpha_threshold
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_alpha_threshold(&mut self, alpha_threshold: u8) {
+        self.alpha_threshold = alpha_threshold;
+    }
+
+    /// Whether the pixel data matches the dimensions
+    pub fn is_valid(&self) -> bool {
+        self.width > 0 && self.height > 0 && self.pixels.len() == 4 * self.width * self.height
+    }
+}
+
+impl SeedImage {
+    fn pixel_size(&self) -> f32 {
+        self.world_width / self.width as f32
+    }
+
+    fn bounds(&self) -> (Vec2, Vec2) {
+        let size = Vec2::new(self.width as f32, self.height as f32) * self.pixel_size();
+        (self.origin, self.origin + size)
+    }
+
+    /// Colour of pixel `index`, packed like `Particle::colour`, if opaque
+    /// enough to seed.
+    fn pixel(&self, index: usize) -> Option<u32> {
+        let rgba = &self.pixels[4 * index..4 * index + 4];
+        (rgba[3] >= self.alpha_threshold)
+            .then(|| u32::from_le_bytes([rgba[0], rgba[1], rgba[2], rgba[3]]))
+    }
+
+    fn colour_at(&self, position: Vec2) -> Option<u32> {
+        let texel = ((position - self.origin) / self.pixel_size()).floor();
+        if texel.x < 0.0 || texel.y < 0.0 {
+            return None;
+        }
+
+        let (column, row_from_bottom) = (texel.x as usize, texel.y as usize);
+        if column >= self.width || row_from_bottom >= self.height {
+            return None;
+        }
+
+        self.pixel((self.height - 1 - row_from_bottom) * self.width + column)
+    }
+}
+
+/// What to fill a `SeedShape` or `SeedImage` with. Images ignore the colour.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug)]
+pub struct SeedOptions {
+    material: usize,
+    /// Particles per unit area
+    density: f32,
+    sampling: Sampling,
+    velocity: Vec2,
+    colour: u32,
+}
+
+#[wasm_bindgen]
+impl SeedOptions {
+    /// White, still particles of `material`, jittered at `density` particles
+    /// per unit area. Four per grid cell is a good starting point. Densities
+    /// that are not finite and positive seed nothing, and one call never
+    /// seeds more than about a million particles.
+    pub fn new(material: usize, density: f32) -> Self {
+        Self {
+            material,
+            density,
+            sampling: Sampling::Jittered,
+            velocity: Vec2::ZERO,
+            colour: 0xffffffff,
+        }
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn material(&self) -> usize {
+        self.material
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn density(&self) -> f32 {
+        self.density
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_density(&mut self, density: f32) {
+        self.density = density;
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn sampling(&self) -> Sampling {
+        self.sampling
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_sampling(&mut self, sampling: Sampling) {
+        self.sampling = sampling;
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn colour(&self) -> u32 {
+        self.colour
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_colour(&mut self, colour: u32) {
+        self.colour = colour;
+    }
+
+    /// Initial velocity of every particle
+    pub fn set_velocity(&mut self, velocity_x: f32, velocity_y: f32) {
+        self.velocity = Vec2::new(velocity_x, velocity_y);
+    }
+}
+
+impl SeedOptions {
+    fn particle(&self, position: Vec2, colour: u32) -> Particle {
+        let mut particle = Particle::new(position, colour, self.material);
+        particle.velocity = self.velocity;
+        particle
+    }
+}
+
+/// Samples particle positions inside `shape` and returns them.
+pub(crate) fn seed<R: Rng>(shape: &SeedShape, options: &SeedOptions, rng: &mut R) -> Vec<Particle> {
+    sample(shape.bounds(), shape.area(), options, rng, |position| {
+        shape.contains(position).then_some(options.colour)
+    })
+}
+
+/// Samples particle positions on the opaque pixels of `image`, each taking
+/// its pixel's colour, and returns them.
+pub(crate) fn seed_image<R: Rng>(
+    image: &SeedImage,
+    options: &SeedOptions,
+    rng: &mut R,
+) -> Vec<Particle> {
+    let opaque = (0..image.width * image.height)
+        .filter(|&i| image.pixel(i).is_some())
+        .count();
+    let area = opaque as f32 * image.pixel_size() * image.pixel_size();
+
+    sample(image.bounds(), area, options, rng, |position| {
+        image.colour_at(position)
+    })
+}
+
+/// Samples particles in the box between `bounds`, keeping those where
+/// `colour_at` gives a colour. `area` is the size of that part of the box.
+fn sample<R: Rng>(
+    (min, max): (Vec2, Vec2),
+    area: f32,
+    options: &SeedOptions,
+    rng: &mut R,
+    colour_at: impl Fn(Vec2) -> Option<u32>,
+) -> Vec<Particle> {
+    let density = options.density;
+    if !(density.is_finite() && density > 0.0 && min.is_finite() && max.is_finite()) {
+        return vec![];
+    }
+
+    let mut particles = vec![];
+    let try_position = |particles: &mut Vec<Particle>, position: Vec2| {
+        if let Some(colour) = colour_at(position) {
+            particles.push(options.particle(position, colour));
+        }
+    };
+
+    match options.sampling {
+        Sampling::Random => {
+            let count = ((area * density).round() as usize).min(MAX_SEEDED);
+            let size = max - min;
+
+            // Rejection sampling from the bounding box, with a cap on attempts
+            // so degenerate shapes cannot loop forever
+            let mut attempts = 0;
+            while attempts < count.saturating_mul(100) {
+                attempts += 1;
+                try_position(&mut particles, min + size * Vec2::new(rng.gen(), rng.gen()));
+                if particles.len() >= count {
+                    break;
+                }
+            }
+        }
+        Sampling::Jittered => {
+            // Widen the spacing until neither axis has more than a
+            // thousand-odd cells, keeping the total under the cap
+            let size = (max - min).max(Vec2::ZERO);
+            let spacing =
+                (1.0 / density.sqrt()).max(size.max_element() / (MAX_SEEDED as f32).sqrt());
+            let cells = (size / spacing).ceil();
+
+            for i in 0..cells.x as usize {
+                for j in 0..cells.y as usize {
+                    let jitter = Vec2::new(rng.gen(), rng.gen());
+                    try_position(
+                        &mut particles,
+                        min + (Vec2::new(i as f32, j as f32) + jitter) * spacing,
+                    );
+                }
+            }
+        }
+    }
+
+    particles
+}
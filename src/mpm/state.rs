@@ -0,0 +1,56 @@
+use super::{grid::Grid, MpmSolver};
+
+/// Identifies saved solver state
+const MAGIC: &[u8; 4] = b"MPMS";
+
+/// Bumped whenever the saved layout changes. Older saves are rejected
+/// rather than misread.
+const VERSION: u32 = 1;
+
+impl MpmSolver {
+    /// Serializes the particles, materials, scene objects, settings and
+    /// random state, so `load_state` can resume exactly where this left off.
+    ///
+    /// The format is a 4 byte magic number and a little-endian `u32` version,
+    /// followed by the solver in bincode.
+    pub fn save_state(&self) -> Vec<u8> {
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend_from_slice(&VERSION.to_le_bytes());
+        bincode::serialize_into(&mut bytes, self).expect("Solver state is always serializable");
+        bytes
+    }
+
+    /// Recreates a solver from the output of `save_state`.
+    pub fn load_state(bytes: &[u8]) -> Result<MpmSolver, String> {
+        if bytes.len() < 8 || &bytes[0..4] != MAGIC {
+            return Err("Not a saved MPM state".into());
+        }
+
+        let version = u32::from_le_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]);
+        if version != VERSION {
+            return Err(format!(
+                "Saved MPM state has version {}, expected {}",
+                version, VERSION
+            ));
+        }
+
+        let mut solver: MpmSolver = bincode::deserialize(&bytes[8..])
+            .map_err(|e| format!("Corrupt saved MPM state: {}", e))?;
+
+        solver.domain.validate()?;
+
+        let materials = solver.materials.len();
+        if solver.particles.iter().any(|p| p.material >= materials)
+            || solver
+                .emitters
+                .iter()
+                .flatten()
+                .any(|e| e.material() >= materials)
+        {
+            return Err("Saved MPM state refers to an unknown material".into());
+        }
+
+        solver.grid = Grid::new(solver.domain.grid_x(), solver.domain.grid_y());
+        Ok(solver)
+    }
+}
@@ -0,0 +1,14 @@
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+/// The generator behind all randomness in the simulations. ChaCha8 produces
+/// the same stream on every platform, so a seed reproduces a scene exactly.
+pub type SimRng = ChaCha8Rng;
+
+/// A generator started from `seed`, or from OS entropy if there is none.
+pub fn new_rng(seed: Option<u64>) -> SimRng {
+    match seed {
+        Some(seed) => SimRng::seed_from_u64(seed),
+        None => SimRng::from_entropy(),
+    }
+}
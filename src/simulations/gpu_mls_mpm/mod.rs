@@ -4,6 +4,7 @@ use crate::{
         VertexArrayObject,
     },
     linalg::{mat_add_scalar, outer_product, polar_decomp, square_vec, svd, Mat2, Vec2},
+    random::{new_rng, SimRng},
 };
 use rand::distributions::{Distribution, Uniform};
 use wasm_bindgen::{prelude::*, JsCast};
@@ -74,21 +75,26 @@ impl GpuMlsMpm {
         canvas: Option<web_sys::Element>,
         num_particles: usize, // per oject
         grid_size: usize,
+        seed: Option<u64>,
     ) -> Result<GpuMlsMpm, JsValue> {
+        let mut rng = new_rng(seed);
         let mut particles = vec![];
         add_particles(
+            &mut rng,
             &mut particles,
             num_particles,
             Vec2::new(0.55, 0.45),
             0xffff00ff,
         );
         add_particles(
+            &mut rng,
             &mut particles,
             num_particles,
             Vec2::new(0.45, 0.65),
             0xff00ff00,
         );
         add_particles(
+            &mut rng,
             &mut particles,
             num_particles,
             Vec2::new(0.55, 0.85),
@@ -157,7 +163,7 @@ impl GpuMlsMpm {
             let lambda = LAMBDA_0 * e;
 
             // Current volume
-            let J = particle.deformation_gradient.determinant();
+            let det = particle.deformation_gradient.determinant();
 
             // Polar decomposition for fixed corotated model
             let (r, _) = polar_decomp(particle.deformation_gradient);
@@ -168,18 +174,18 @@ impl GpuMlsMpm {
                 2.0 * mu
                     * (particle.deformation_gradient - r)
                     * particle.deformation_gradient.transpose(),
-                lambda * (J - 1.0) * J,
+                lambda * (det - 1.0) * det,
             );
             let stress = -(dt * VOL) * (d_inv * p_f);
             let affine = stress + (PARTICLE_MASS * particle.apic_affine_momentum);
 
             // Translational momentum
 
-            for i in 0..3 {
-                for j in 0..3 {
+            for (i, wi) in w.iter().enumerate() {
+                for (j, wj) in w.iter().enumerate() {
                     let dpos = (Vec2::new(i as f32, j as f32) - fx) * dx;
 
-                    let factor = w[i].x * w[j].y;
+                    let factor = wi.x * wj.y;
                     let affine_times_dpos = affine * dpos;
 
                     let cell = &mut grid[base_coord.x as usize + i][base_coord.y as usize + j];
@@ -192,9 +198,8 @@ impl GpuMlsMpm {
         }
 
         // For all grid nodes
-        for i in 0..=self.grid_size {
-            for j in 0..=self.grid_size {
-                let cell = &mut grid[i][j];
+        for (i, column) in grid.iter_mut().enumerate() {
+            for (j, cell) in column.iter_mut().enumerate() {
                 if cell.mass <= 0.0 {
                     continue;
                 }
@@ -241,12 +246,12 @@ impl GpuMlsMpm {
             particle.apic_affine_momentum = Mat2::ZERO;
             particle.velocity = Vec2::ZERO;
 
-            for i in 0..3 {
-                for j in 0..3 {
+            for (i, wi) in w.iter().enumerate() {
+                for (j, wj) in w.iter().enumerate() {
                     let dpos = Vec2::new(i as f32, j as f32) - fx;
                     let grid_v =
                         grid[base_coord.x as usize + i][base_coord.y as usize + j].velocity;
-                    let weight = w[i].x * w[j].y;
+                    let weight = wi.x * wj.y;
 
                     // Velocity
                     particle.velocity += weight * grid_v;
@@ -260,10 +265,10 @@ impl GpuMlsMpm {
             particle.position += dt * particle.velocity;
 
             // MLS-MPM F-update
-            let F = (Mat2::IDENTITY + particle.apic_affine_momentum * dt)
+            let trial_f = (Mat2::IDENTITY + particle.apic_affine_momentum * dt)
                 * particle.deformation_gradient;
 
-            let (svd_u, mut sig, svd_v) = svd(F);
+            let (svd_u, mut sig, svd_v) = svd(trial_f);
 
             // Snow plasticity
             if PLASTIC {
@@ -271,13 +276,13 @@ impl GpuMlsMpm {
                 sig.col_mut(1).y = sig.col_mut(1).y.clamp(1.0 - 2.5e-2, 1.0 + 7.5e-3);
             }
 
-            let old_j = F.determinant();
-            let F = svd_u * sig * svd_v.transpose();
+            let old_j = trial_f.determinant();
+            let f = svd_u * sig * svd_v.transpose();
 
             particle.deformation_gradient_det =
-                (particle.deformation_gradient_det * old_j / F.determinant()).clamp(0.6, 20.0);
+                (particle.deformation_gradient_det * old_j / f.determinant()).clamp(0.6, 20.0);
 
-            particle.deformation_gradient = F;
+            particle.deformation_gradient = f;
         }
     }
 
@@ -297,7 +302,7 @@ impl GpuMlsMpm {
         for p in self.particles.iter() {
             data.push(p.position.x);
             data.push(p.position.y);
-            data.push(unsafe { std::mem::transmute::<u32, f32>(p.colour) });
+            data.push(f32::from_bits(p.colour));
         }
 
         upload_array_buffer(&self.ctx, &data, &self.buffer);
@@ -340,10 +345,10 @@ struct DrawProgram {
 impl DrawProgram {
     fn new(ctx: &Context) -> Result<Self, JsValue> {
         let program = {
-            let vert_shader = Shader::new_vert(&ctx, include_str!("draw_vert.glsl"))?;
-            let frag_shader = Shader::new_frag(&ctx, include_str!("draw_frag.glsl"))?;
+            let vert_shader = Shader::new_vert(ctx, include_str!("draw_vert.glsl"))?;
+            let frag_shader = Shader::new_frag(ctx, include_str!("draw_frag.glsl"))?;
 
-            Program::new(&ctx, &[&vert_shader, &frag_shader], None)?
+            Program::new(ctx, &[&vert_shader, &frag_shader], None)?
         };
 
         let attrib_info_position = AttribInfo {
@@ -367,12 +372,17 @@ impl DrawProgram {
     }
 }
 
-fn add_particles(v: &mut Vec<Particle>, num_particles: usize, center: Vec2, c: u32) {
-    let mut rng = rand::thread_rng();
+fn add_particles(
+    rng: &mut SimRng,
+    v: &mut Vec<Particle>,
+    num_particles: usize,
+    center: Vec2,
+    c: u32,
+) {
     let range = Uniform::from(-1.0..=1.0);
 
     (0..num_particles).for_each(|_| {
-        let pos = Vec2::new(range.sample(&mut rng), range.sample(&mut rng));
+        let pos = Vec2::new(range.sample(rng), range.sample(rng));
         let pos = pos * 0.08 + center;
 
         v.push(Particle::new(pos, c));
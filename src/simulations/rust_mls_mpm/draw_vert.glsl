@@ -5,10 +5,14 @@ precision mediump float;
 in vec2 i_Position;
 in vec4 i_Color;
 
+// World to clip space: clip = (world - u_Center) * u_Scale
+uniform vec2 u_Scale;
+uniform vec2 u_Center;
+
 out vec4 o_Color;
 
 void main() {
   o_Color = i_Color;
   gl_PointSize = 5.0;
-  gl_Position = vec4((i_Position  * vec2(2,2)- vec2(1, 1)), 0.0, 1.0);
+  gl_Position = vec4((i_Position - u_Center) * u_Scale, 0.0, 1.0);
 }
\ No newline at end of file
@@ -3,69 +3,29 @@ use crate::{
         setup_array_buffer_vao, AttribInfo, Buffer, BufferInfo, Colour, Context, Program, Shader,
         VertexArrayObject,
     },
-    linalg::{mat_add_scalar, outer_product, polar_decomp, square_vec, svd, Mat2, Vec2},
+    mpm::{
+        Collider, Domain, Emitter, MaterialParams, MpmSolver, Playback, Pointer, PointerMode,
+        RigidBody, SeedImage, SeedOptions, SeedShape, SimParams, Sink, UpdateReport, Vec2,
+    },
 };
-use rand::distributions::{Distribution, Uniform};
 use wasm_bindgen::{prelude::*, JsCast};
 
-// Snow material properties
-const PARTICLE_MASS: f32 = 1.0;
-const VOL: f32 = 1.0; // Particle Volume
-const HARDENING: f32 = 10.0; // Snow hardening factor
-const E: f32 = 10000.0; // Young's Modulus
-const NU: f32 = 0.2; // Poisson ratio
-const PLASTIC: bool = false;
-
-// Initial Lamé parameters
-const MU_0: f32 = E / (2.0 * (1.0 + NU));
-const LAMBDA_0: f32 = E * NU / ((1.0 + NU) * (1.0 - 2.0 * NU));
-
-struct Particle {
-    position: Vec2,
-    velocity: Vec2,
-    deformation_gradient: Mat2,
-    apic_affine_momentum: Mat2,
-    deformation_gradient_det: f32,
-    colour: u32,
-}
-
-impl Particle {
-    fn new(pos: Vec2, colour: u32) -> Self {
-        Self {
-            position: pos,
-            velocity: Vec2::ZERO,
-            deformation_gradient: Mat2::IDENTITY,
-            apic_affine_momentum: Mat2::ZERO,
-            deformation_gradient_det: 1.0,
-            colour,
-        }
-    }
-}
-
-#[derive(Clone, Debug)]
-struct Cell {
-    velocity: Vec2,
-    mass: f32,
-}
-
-impl Default for Cell {
-    fn default() -> Self {
-        Self {
-            velocity: Vec2::ZERO,
-            mass: 0.0,
-        }
-    }
-}
-
 #[wasm_bindgen]
 pub struct RustMlsMpm {
     ctx: Context,
     draw_program: DrawProgram,
-    particles: Vec<Particle>,
-    grid_size: usize,
+    solver: MpmSolver,
     buffer: Buffer,
     vao: VertexArrayObject,
     frame_number: usize,
+    /// Interleaved vertex data, kept to avoid reallocating every frame
+    vertex_data: Vec<f32>,
+    /// Where the last pointer event was, before any gliding
+    pointer_event: Option<Vec2>,
+    /// Simulated time since the pointer last moved, to derive its velocity
+    pointer_time: f32,
+    /// A recording being shown in place of the simulation
+    replay: Option<Playback>,
 }
 
 #[wasm_bindgen]
@@ -74,26 +34,40 @@ impl RustMlsMpm {
         canvas: Option<web_sys::Element>,
         num_particles: usize, // per oject
         grid_size: usize,
+        material: Option<MaterialParams>,
+        params: Option<SimParams>,
+        seed: Option<u64>,
     ) -> Result<RustMlsMpm, JsValue> {
-        let mut particles = vec![];
-        add_particles(
-            &mut particles,
-            num_particles,
-            Vec2::new(0.55, 0.45),
-            0xffff00ff,
-        );
-        add_particles(
-            &mut particles,
-            num_particles,
-            Vec2::new(0.45, 0.65),
-            0xff00ff00,
-        );
-        add_particles(
-            &mut particles,
+        if grid_size == 0 {
+            return Err("Grid size must be at least one".into());
+        }
+
+        Self::with_domain(
+            canvas,
             num_particles,
-            Vec2::new(0.55, 0.85),
-            0xffff0000,
-        );
+            Domain::unit_square(grid_size),
+            material,
+            params,
+            seed,
+        )
+    }
+
+    pub fn with_domain(
+        canvas: Option<web_sys::Element>,
+        num_particles: usize, // per oject
+        domain: Domain,
+        material: Option<MaterialParams>,
+        params: Option<SimParams>,
+        seed: Option<u64>,
+    ) -> Result<RustMlsMpm, JsValue> {
+        let mut solver = MpmSolver::new(domain, params.unwrap_or_default(), seed);
+        let material = solver.add_material(material.unwrap_or_default());
+
+        // Blobs are placed relative to the domain
+        let at = |x, y| domain.origin() + Vec2::new(x, y) * domain.size();
+        solver.add_particles(num_particles, at(0.55, 0.45), 0xffff00ff, material);
+        solver.add_particles(num_particles, at(0.45, 0.65), 0xff00ff00, material);
+        solver.add_particles(num_particles, at(0.55, 0.85), 0xffff0000, material);
 
         let canvas = match canvas {
             Some(element) => element.dyn_into::<web_sys::HtmlCanvasElement>()?,
@@ -122,166 +96,357 @@ impl RustMlsMpm {
         Ok(Self {
             ctx,
             draw_program,
-            particles,
-            grid_size,
+            solver,
             buffer,
             vao,
             frame_number: 0,
+            vertex_data: vec![],
+            pointer_event: None,
+            pointer_time: 0.0,
+            replay: None,
         })
     }
 
-    pub fn advance(&mut self, dt: f32) {
-        let mut grid = vec![vec![Cell::default(); self.grid_size + 1]; self.grid_size + 1];
-        let dx = 1.0 / self.grid_size as f32;
-        let inv_dx = self.grid_size as f32;
+    /// Registers a new material and returns its id, for use with `add_particles`.
+    pub fn add_material(&mut self, material: MaterialParams) -> usize {
+        self.solver.add_material(material)
+    }
+
+    pub fn material(&self, id: usize) -> Result<MaterialParams, JsValue> {
+        match self.solver.material(id) {
+            Some(material) => Ok(*material),
+            None => Err("Material id out of range".into()),
+        }
+    }
 
-        // Particles to grid
-        for particle in self.particles.iter() {
-            // Elementwise floor
-            let base_coord = (particle.position * inv_dx - Vec2::splat(0.5))
-                .floor()
-                .as_ivec2();
+    pub fn set_material(&mut self, id: usize, material: MaterialParams) -> Result<(), JsValue> {
+        match self.solver.material_mut(id) {
+            Some(existing) => {
+                *existing = material;
+                Ok(())
+            }
+            None => Err("Material id out of range".into()),
+        }
+    }
 
-            let fx = particle.position * inv_dx - base_coord.as_vec2();
+    pub fn add_particles(
+        &mut self,
+        num_particles: usize,
+        center_x: f32,
+        center_y: f32,
+        colour: u32,
+        material: usize,
+    ) -> Result<(), JsValue> {
+        if self.solver.material(material).is_none() {
+            return Err("Material id out of range".into());
+        }
+        self.solver.add_particles(
+            num_particles,
+            Vec2::new(center_x, center_y),
+            colour,
+            material,
+        );
+        Ok(())
+    }
 
-            // Quadratic kernels
-            let w = [
-                Vec2::splat(0.5) * square_vec(Vec2::splat(1.5) - fx),
-                Vec2::splat(0.75) - square_vec(fx - Vec2::ONE),
-                Vec2::splat(0.5) * square_vec(fx - Vec2::splat(0.5)),
-            ];
+    /// Adds an obstacle and returns its id, for use with `remove_collider`.
+    pub fn add_collider(&mut self, collider: Collider) -> usize {
+        self.solver.add_collider(collider)
+    }
 
-            // Lamé parameters
-            let e = (HARDENING * (1.0 - particle.deformation_gradient_det)).exp();
-            let mu = MU_0 * e;
-            let lambda = LAMBDA_0 * e;
+    pub fn remove_collider(&mut self, id: usize) -> Result<(), JsValue> {
+        match self.solver.remove_collider(id) {
+            Some(_) => Ok(()),
+            None => Err("Collider id out of range".into()),
+        }
+    }
 
-            // Current volume
-            let J = particle.deformation_gradient.determinant();
+    pub fn collider(&self, id: usize) -> Result<Collider, JsValue> {
+        match self.solver.collider(id) {
+            Some(collider) => Ok(*collider),
+            None => Err("Collider id out of range".into()),
+        }
+    }
 
-            // Polar decomposition for fixed corotated model
-            let (r, _) = polar_decomp(particle.deformation_gradient);
+    /// Moves a collider. Call every frame, along with `set_collider_velocity`,
+    /// to drive it from JS.
+    pub fn set_collider_pose(
+        &mut self,
+        id: usize,
+        x: f32,
+        y: f32,
+        rotation: f32,
+    ) -> Result<(), JsValue> {
+        match self.solver.collider_mut(id) {
+            Some(collider) => {
+                collider.set_pose(x, y, rotation);
+                Ok(())
+            }
+            None => Err("Collider id out of range".into()),
+        }
+    }
 
-            let d_inv = 4.0 * inv_dx * inv_dx;
+    /// Sets the velocity a collider moves with, and imparts, between poses.
+    pub fn set_collider_velocity(
+        &mut self,
+        id: usize,
+        velocity_x: f32,
+        velocity_y: f32,
+        angular_velocity: f32,
+    ) -> Result<(), JsValue> {
+        match self.solver.collider_mut(id) {
+            Some(collider) => {
+                collider.set_velocity(velocity_x, velocity_y, angular_velocity);
+                Ok(())
+            }
+            None => Err("Collider id out of range".into()),
+        }
+    }
 
-            let p_f = mat_add_scalar(
-                2.0 * mu
-                    * (particle.deformation_gradient - r)
-                    * particle.deformation_gradient.transpose(),
-                lambda * (J - 1.0) * J,
-            );
-            let stress = -(dt * VOL) * (d_inv * p_f);
-            let affine = stress + (PARTICLE_MASS * particle.apic_affine_momentum);
+    /// Adds a body that is pushed by, and pushes, the material. Returns its
+    /// id, for use with `rigid_body` and `remove_rigid_body`.
+    pub fn add_rigid_body(&mut self, body: RigidBody) -> usize {
+        self.solver.add_rigid_body(body)
+    }
 
-            // Translational momentum
+    pub fn remove_rigid_body(&mut self, id: usize) -> Result<(), JsValue> {
+        match self.solver.remove_rigid_body(id) {
+            Some(_) => Ok(()),
+            None => Err("Rigid body id out of range".into()),
+        }
+    }
 
-            for i in 0..3 {
-                for j in 0..3 {
-                    let dpos = (Vec2::new(i as f32, j as f32) - fx) * dx;
+    /// The body's current state, including its pose for drawing.
+    pub fn rigid_body(&self, id: usize) -> Result<RigidBody, JsValue> {
+        match self.solver.rigid_body(id) {
+            Some(body) => Ok(*body),
+            None => Err("Rigid body id out of range".into()),
+        }
+    }
 
-                    let factor = w[i].x * w[j].y;
-                    let affine_times_dpos = affine * dpos;
+    /// Adds a particle source and returns its id.
+    pub fn add_emitter(&mut self, emitter: Emitter) -> Result<usize, JsValue> {
+        if self.solver.material(emitter.material()).is_none() {
+            return Err("Material id out of range".into());
+        }
+        Ok(self.solver.add_emitter(emitter))
+    }
 
-                    let cell = &mut grid[base_coord.x as usize + i][base_coord.y as usize + j];
+    pub fn remove_emitter(&mut self, id: usize) -> Result<(), JsValue> {
+        match self.solver.remove_emitter(id) {
+            Some(_) => Ok(()),
+            None => Err("Emitter id out of range".into()),
+        }
+    }
 
-                    cell.velocity +=
-                        (particle.velocity * PARTICLE_MASS + affine_times_dpos) * factor;
-                    cell.mass += PARTICLE_MASS * factor;
-                }
+    /// Changes an emitter's flow, e.g. to turn a faucet up, down or off.
+    pub fn set_emitter_rate(&mut self, id: usize, rate: f32) -> Result<(), JsValue> {
+        match self.solver.emitter_mut(id) {
+            Some(emitter) => {
+                emitter.set_rate(rate);
+                Ok(())
             }
+            None => Err("Emitter id out of range".into()),
         }
+    }
 
-        // For all grid nodes
-        for i in 0..=self.grid_size {
-            for j in 0..=self.grid_size {
-                let cell = &mut grid[i][j];
-                if cell.mass <= 0.0 {
-                    continue;
-                }
+    /// Adds a region that deletes particles and returns its id.
+    pub fn add_sink(&mut self, sink: Sink) -> usize {
+        self.solver.add_sink(sink)
+    }
 
-                // Normalise by mass
-                cell.velocity /= cell.mass;
-                cell.mass = 1.0;
+    pub fn remove_sink(&mut self, id: usize) -> Result<(), JsValue> {
+        match self.solver.remove_sink(id) {
+            Some(_) => Ok(()),
+            None => Err("Sink id out of range".into()),
+        }
+    }
 
-                // Gravity
-                cell.velocity += Vec2::new(0.0, -200.0 * dt);
+    /// Points at the material from a canvas event, in CSS pixels from the
+    /// canvas' top-left corner as given by `offsetX`/`offsetY`. The radius is
+    /// in world units, and the strength is the push/pull acceleration.
+    pub fn set_pointer(
+        &mut self,
+        canvas_x: f32,
+        canvas_y: f32,
+        radius: f32,
+        mode: PointerMode,
+        strength: f32,
+    ) -> Result<(), JsValue> {
+        let position = self.canvas_to_world(Vec2::new(canvas_x, canvas_y))?;
+        let mut pointer = Pointer::new(position.x, position.y, radius, mode, strength);
+
+        // Grabbed material follows the pointer's motion since the last event.
+        // Events that arrive before any time has passed keep the motion the
+        // pointer already had.
+        if let (Some(last_event), Some(previous)) = (self.pointer_event, self.solver.pointer()) {
+            if self.pointer_time > 0.0 {
+                let velocity = (position - last_event) / self.pointer_time;
+                pointer.set_velocity(velocity.x, velocity.y, self.pointer_time);
+            } else {
+                let velocity = previous.velocity();
+                pointer.set_velocity(velocity.x, velocity.y, previous.glide_time());
+            }
+        }
 
-                // Boundary thickness
-                let boundary = 0.05;
-                let x = i as f32 / self.grid_size as f32;
-                let y = j as f32 / self.grid_size as f32;
+        self.solver.set_pointer(Some(pointer));
+        self.pointer_event = Some(position);
+        self.pointer_time = 0.0;
+        Ok(())
+    }
 
-                // Sticky boundary
-                if x < boundary || x > 1.0 - boundary || y > 1.0 - boundary {
-                    cell.velocity = Vec2::ZERO;
-                    cell.mass = 0.0;
-                }
-                // Separate boundary
-                if y < boundary {
-                    cell.velocity.y = cell.velocity.y.max(0.0);
-                }
-            }
+    /// Stops interacting, e.g. on `pointerup`.
+    pub fn clear_pointer(&mut self) {
+        self.solver.set_pointer(None);
+        self.pointer_event = None;
+    }
+
+    /// Fills a shape with particles, returning how many were added.
+    pub fn seed(&mut self, shape: &SeedShape, options: &SeedOptions) -> Result<usize, JsValue> {
+        if self.solver.material(options.material()).is_none() {
+            return Err("Material id out of range".into());
         }
+        Ok(self.solver.seed(shape, options))
+    }
 
-        // Grid to particles
-        for particle in self.particles.iter_mut() {
-            // Elementwise floor
-            let base_coord = (particle.position * inv_dx - Vec2::splat(0.5))
-                .floor()
-                .as_ivec2();
-            let fx = particle.position * inv_dx - base_coord.as_vec2();
-
-            // Quadratic kernels
-            let w = [
-                Vec2::splat(0.5) * square_vec(Vec2::splat(1.5) - fx),
-                Vec2::splat(0.75) - square_vec(fx - Vec2::ONE),
-                Vec2::splat(0.5) * square_vec(fx - Vec2::splat(0.5)),
-            ];
-
-            particle.apic_affine_momentum = Mat2::ZERO;
-            particle.velocity = Vec2::ZERO;
-
-            for i in 0..3 {
-                for j in 0..3 {
-                    let dpos = Vec2::new(i as f32, j as f32) - fx;
-                    let grid_v =
-                        grid[base_coord.x as usize + i][base_coord.y as usize + j].velocity;
-                    let weight = w[i].x * w[j].y;
-
-                    // Velocity
-                    particle.velocity += weight * grid_v;
-                    // APIC C
-                    particle.apic_affine_momentum +=
-                        4.0 * inv_dx * outer_product(weight * grid_v, dpos);
-                }
-            }
+    /// Turns an image's opaque pixels into particles of its colours,
+    /// returning how many were added.
+    pub fn seed_image(
+        &mut self,
+        image: &SeedImage,
+        options: &SeedOptions,
+    ) -> Result<usize, JsValue> {
+        if self.solver.material(options.material()).is_none() {
+            return Err("Material id out of range".into());
+        }
+        if !image.is_valid() {
+            return Err("Image data does not match its size".into());
+        }
+        Ok(self.solver.seed_image(image, options))
+    }
 
-            // Advection
-            particle.position += dt * particle.velocity;
+    /// Snapshot of the whole simulation, for `load_state` here or in another
+    /// instance.
+    pub fn save_state(&self) -> Vec<u8> {
+        self.solver.save_state()
+    }
 
-            // MLS-MPM F-update
-            let F = (Mat2::IDENTITY + particle.apic_affine_momentum * dt)
-                * particle.deformation_gradient;
+    /// Replaces the simulation with one saved by `save_state`.
+    pub fn load_state(&mut self, bytes: &[u8]) -> Result<(), JsValue> {
+        self.solver = MpmSolver::load_state(bytes)?;
+        self.pointer_event = None;
+        self.pointer_time = 0.0;
+        Ok(())
+    }
 
-            let (svd_u, mut sig, svd_v) = svd(F);
+    /// Particles of the current simulation as a legacy VTK file, for
+    /// ParaView.
+    pub fn export_vtk(&self) -> String {
+        self.solver.export_vtk()
+    }
 
-            // Snow plasticity
-            if PLASTIC {
-                sig.col_mut(0).x = sig.col_mut(0).x.clamp(1.0 - 2.5e-2, 1.0 + 7.5e-3);
-                sig.col_mut(1).y = sig.col_mut(1).y.clamp(1.0 - 2.5e-2, 1.0 + 7.5e-3);
-            }
+    /// Particles of the current simulation as a binary PLY file, for
+    /// Blender.
+    pub fn export_ply(&self) -> Vec<u8> {
+        self.solver.export_ply()
+    }
 
-            let old_j = F.determinant();
-            let F = svd_u * sig * svd_v.transpose();
+    /// Grid mass and velocity from the last step as a legacy VTK file.
+    pub fn export_grid_vtk(&self) -> String {
+        self.solver.export_grid_vtk()
+    }
 
-            particle.deformation_gradient_det =
-                (particle.deformation_gradient_det * old_j / F.determinant()).clamp(0.6, 20.0);
+    /// Records particle positions and colours every `every` steps, until
+    /// `stop_recording`. Quantized recordings are several times smaller, at
+    /// 1/65535 of the domain's precision.
+    pub fn start_recording(&mut self, quantized: bool, every: u32) {
+        self.solver.start_recording(quantized, every);
+    }
 
-            particle.deformation_gradient = F;
+    /// Ends the recording and returns it, for `start_replay`.
+    pub fn stop_recording(&mut self) -> Result<Vec<u8>, JsValue> {
+        match self.solver.stop_recording() {
+            Some(bytes) => Ok(bytes),
+            None => Err("Not recording".into()),
         }
     }
 
-    pub fn draw(&mut self, dt: f32) -> Result<(), JsValue> {
+    /// Shows a recording instead of simulating, playing at the recorded
+    /// speed scaled by the time scale, until `stop_replay`.
+    pub fn start_replay(&mut self, bytes: Vec<u8>) -> Result<(), JsValue> {
+        self.replay = Some(Playback::new(bytes)?);
+        Ok(())
+    }
+
+    pub fn stop_replay(&mut self) {
+        self.replay = None;
+    }
+
+    pub fn replay_frame_count(&self) -> Result<usize, JsValue> {
+        match &self.replay {
+            Some(replay) => Ok(replay.frame_count()),
+            None => Err("Not replaying".into()),
+        }
+    }
+
+    pub fn replay_frame(&self) -> Result<usize, JsValue> {
+        match &self.replay {
+            Some(replay) => Ok(replay.frame()),
+            None => Err("Not replaying".into()),
+        }
+    }
+
+    /// Jumps to a frame of the replay, e.g. from a scrubber.
+    pub fn seek_replay(&mut self, frame: usize) -> Result<(), JsValue> {
+        match &mut self.replay {
+            Some(replay) => {
+                replay.seek(frame);
+                Ok(())
+            }
+            None => Err("Not replaying".into()),
+        }
+    }
+
+    pub fn params(&self) -> SimParams {
+        *self.solver.params()
+    }
+
+    pub fn set_params(&mut self, params: SimParams) {
+        self.solver.set_params(params);
+    }
+
+    /// Steps the simulation by at most `dt`, returning the step actually taken.
+    pub fn advance(&mut self, dt: f32) -> f32 {
+        let dt = self.solver.step(dt);
+        self.pointer_time += dt;
+        dt
+    }
+
+    /// Advances the simulation by `real_dt` wall-clock seconds in fixed
+    /// substeps, so it runs at the same speed at any frame rate.
+    pub fn update(&mut self, real_dt: f32) -> UpdateReport {
+        let report = self.solver.update(real_dt);
+        self.pointer_time += report.simulated_time();
+        report
+    }
+
+    /// The time step chosen for the most recent step.
+    pub fn last_dt(&self) -> f32 {
+        self.solver.last_dt()
+    }
+
+    /// The largest time step the CFL condition allows right now.
+    pub fn stable_dt(&self) -> f32 {
+        self.solver.stable_dt()
+    }
+
+    /// Number of sparse grid blocks in use, for profiling.
+    pub fn active_grid_blocks(&self) -> usize {
+        self.solver.active_grid_blocks()
+    }
+
+    /// Updates by `real_dt` wall-clock seconds and renders the result.
+    pub fn draw(&mut self, real_dt: f32) -> Result<UpdateReport, JsValue> {
         self.ctx.clear_colour_buffer(Colour {
             red: 0.9,
             green: 0.8,
@@ -290,17 +455,34 @@ impl RustMlsMpm {
         });
 
         self.ctx.use_program(&self.draw_program.program);
+        self.set_view_transform();
+
+        // The particle count changes with emitters and sinks, so the vertex
+        // data and buffer are resized to match every frame
+        self.vertex_data.clear();
+
+        let report = match &mut self.replay {
+            Some(replay) => {
+                replay.advance(real_dt * self.solver.params().time_scale());
+                for (position, colour) in replay.positions().iter().zip(replay.colours()) {
+                    self.vertex_data.push(position.x);
+                    self.vertex_data.push(position.y);
+                    self.vertex_data.push(f32::from_bits(*colour));
+                }
+                UpdateReport::default()
+            }
+            None => {
+                let report = self.update(real_dt);
+                for p in self.solver.particles() {
+                    self.vertex_data.push(p.position.x);
+                    self.vertex_data.push(p.position.y);
+                    self.vertex_data.push(f32::from_bits(p.colour));
+                }
+                report
+            }
+        };
 
-        self.advance(dt);
-
-        let mut data = vec![];
-        for p in self.particles.iter() {
-            data.push(p.position.x);
-            data.push(p.position.y);
-            data.push(unsafe { std::mem::transmute::<u32, f32>(p.colour) });
-        }
-
-        upload_array_buffer(&self.ctx, &data, &self.buffer);
+        upload_array_buffer(&self.ctx, &self.vertex_data, &self.buffer);
 
         // /* Now, we draw the particle system. Note that we're actually
         // drawing the data from the "read" buffer, not the "write" buffer
@@ -309,12 +491,57 @@ impl RustMlsMpm {
         self.ctx.0.draw_arrays(
             web_sys::WebGl2RenderingContext::POINTS,
             0,
-            self.particles.len() as i32,
+            (self.vertex_data.len() / 3) as i32,
         );
 
         self.frame_number += 1;
 
-        Ok(())
+        Ok(report)
+    }
+}
+
+impl RustMlsMpm {
+    /// World to clip space as `(world - center) * scale`, fitting the domain
+    /// to the canvas, centred and with square pixels.
+    fn view_transform(&self) -> (Vec2, Vec2) {
+        let (min, max) = match &self.replay {
+            Some(replay) => replay.bounds(),
+            None => {
+                let domain = self.solver.domain();
+                (domain.origin(), domain.origin() + domain.size())
+            }
+        };
+        let canvas = Vec2::new(
+            self.ctx.0.drawing_buffer_width() as f32,
+            self.ctx.0.drawing_buffer_height() as f32,
+        );
+
+        // Pixels per world unit
+        let zoom = (canvas / (max - min)).min_element();
+        (2.0 * zoom / canvas, 0.5 * (min + max))
+    }
+
+    fn set_view_transform(&self) {
+        let (scale, center) = self.view_transform();
+        self.ctx
+            .set_uniform_vec2(&self.draw_program.uniform_scale, scale.x, scale.y);
+        self.ctx
+            .set_uniform_vec2(&self.draw_program.uniform_center, center.x, center.y);
+    }
+
+    /// Converts CSS pixels on the canvas to world coordinates.
+    fn canvas_to_world(&self, position: Vec2) -> Result<Vec2, JsValue> {
+        let canvas = match self.ctx.0.canvas() {
+            Some(canvas) => canvas.dyn_into::<web_sys::HtmlCanvasElement>()?,
+            None => return Err("Context has no canvas".into()),
+        };
+        let size = Vec2::new(canvas.client_width() as f32, canvas.client_height() as f32);
+
+        // Clip space has y pointing up, CSS pixels have it pointing down
+        let clip = Vec2::new(2.0, -2.0) * position / size + Vec2::new(-1.0, 1.0);
+
+        let (scale, center) = self.view_transform();
+        Ok(clip / scale + center)
     }
 }
 
@@ -335,15 +562,17 @@ struct DrawProgram {
     program: Program,
     attrib_info_position: AttribInfo,
     attrib_info_colour: AttribInfo,
+    uniform_scale: web_sys::WebGlUniformLocation,
+    uniform_center: web_sys::WebGlUniformLocation,
 }
 
 impl DrawProgram {
     fn new(ctx: &Context) -> Result<Self, JsValue> {
         let program = {
-            let vert_shader = Shader::new_vert(&ctx, include_str!("draw_vert.glsl"))?;
-            let frag_shader = Shader::new_frag(&ctx, include_str!("draw_frag.glsl"))?;
+            let vert_shader = Shader::new_vert(ctx, include_str!("draw_vert.glsl"))?;
+            let frag_shader = Shader::new_frag(ctx, include_str!("draw_frag.glsl"))?;
 
-            Program::new(&ctx, &[&vert_shader, &frag_shader], None)?
+            Program::new(ctx, &[&vert_shader, &frag_shader], None)?
         };
 
         let attrib_info_position = AttribInfo {
@@ -359,22 +588,15 @@ impl DrawProgram {
             normalised: true,
         };
 
+        let uniform_scale = ctx.get_uniform_location(&program, "u_Scale")?;
+        let uniform_center = ctx.get_uniform_location(&program, "u_Center")?;
+
         Ok(Self {
             program,
             attrib_info_position,
             attrib_info_colour,
+            uniform_scale,
+            uniform_center,
         })
     }
 }
-
-fn add_particles(v: &mut Vec<Particle>, num_particles: usize, center: Vec2, c: u32) {
-    let mut rng = rand::thread_rng();
-    let range = Uniform::from(-1.0..=1.0);
-
-    (0..num_particles).for_each(|_| {
-        let pos = Vec2::new(range.sample(&mut rng), range.sample(&mut rng));
-        let pos = pos * 0.08 + center;
-
-        v.push(Particle::new(pos, c));
-    });
-}
@@ -2,6 +2,7 @@ use crate::gl::{
     setup_array_buffer_vao, AttribInfo, Buffer, BufferInfo, Colour, Context, Program, Shader,
     Texture, TransformFeedbackVaryings, VertexArrayObject,
 };
+use crate::random::{new_rng, SimRng};
 use rand::distributions::{Distribution, Uniform};
 use wasm_bindgen::{prelude::*, JsCast};
 
@@ -31,7 +32,10 @@ impl StaticParticles {
         particle_birth_rate: usize,
         gravity_x: f32,
         gravity_y: f32,
+        seed: Option<u64>,
     ) -> Result<StaticParticles, JsValue> {
+        let mut rng = new_rng(seed);
+
         let canvas = match canvas {
             Some(element) => element.dyn_into::<web_sys::HtmlCanvasElement>()?,
             None => return Err("Canvas element does not exist".into()),
@@ -50,7 +54,7 @@ impl StaticParticles {
             VertexArrayObject::new(&ctx)?,
         ];
 
-        let data = initial_particle_data(num_particles, 1.0, 2.0);
+        let data = initial_particle_data(&mut rng, num_particles, 1.0, 2.0);
         {
             let src_data = unsafe { js_sys::Float32Array::view(&data) };
 
@@ -142,7 +146,7 @@ impl StaticParticles {
             Some(&rg_noise_texture.0),
         );
 
-        ctx.tex_image_2d(512, 512, &random_rg_data(512, 512))?;
+        ctx.tex_image_2d(512, 512, &random_rg_data(&mut rng, 512, 512))?;
         ctx.0.tex_parameteri(
             web_sys::WebGl2RenderingContext::TEXTURE_2D,
             web_sys::WebGl2RenderingContext::TEXTURE_WRAP_S,
@@ -371,11 +375,11 @@ pub struct UpdateProgram {
 impl UpdateProgram {
     fn new(ctx: &Context) -> Result<Self, JsValue> {
         let program = {
-            let vert_shader = Shader::new_vert(&ctx, include_str!("update_vert.glsl"))?;
-            let frag_shader = Shader::new_frag(&ctx, include_str!("update_frag.glsl"))?;
+            let vert_shader = Shader::new_vert(ctx, include_str!("update_vert.glsl"))?;
+            let frag_shader = Shader::new_frag(ctx, include_str!("update_frag.glsl"))?;
 
             Program::new(
-                &ctx,
+                ctx,
                 &[&vert_shader, &frag_shader],
                 Some(TransformFeedbackVaryings {
                     names: &["v_Position", "v_Age", "v_Life", "v_Velocity"],
@@ -429,10 +433,10 @@ struct DrawProgram {
 impl DrawProgram {
     fn new(ctx: &Context) -> Result<Self, JsValue> {
         let program = {
-            let vert_shader = Shader::new_vert(&ctx, include_str!("draw_vert.glsl"))?;
-            let frag_shader = Shader::new_frag(&ctx, include_str!("draw_frag.glsl"))?;
+            let vert_shader = Shader::new_vert(ctx, include_str!("draw_vert.glsl"))?;
+            let frag_shader = Shader::new_frag(ctx, include_str!("draw_frag.glsl"))?;
 
-            Program::new(&ctx, &[&vert_shader, &frag_shader], None)?
+            Program::new(ctx, &[&vert_shader, &frag_shader], None)?
         };
 
         let attrib_info_position = AttribInfo {
@@ -465,10 +469,14 @@ impl DrawProgram {
     }
 }
 
-fn initial_particle_data(num_parts: usize, min_age: f32, max_age: f32) -> Vec<f32> {
+fn initial_particle_data(
+    rng: &mut SimRng,
+    num_parts: usize,
+    min_age: f32,
+    max_age: f32,
+) -> Vec<f32> {
     let mut data = vec![];
 
-    let mut rng = rand::thread_rng();
     let die = Uniform::from(min_age..=max_age);
 
     (0..num_parts).for_each(|_| {
@@ -476,7 +484,7 @@ fn initial_particle_data(num_parts: usize, min_age: f32, max_age: f32) -> Vec<f3
         data.push(0.0);
         data.push(0.0);
 
-        let life = die.sample(&mut rng) as f32;
+        let life = die.sample(rng);
         data.push(life + 1.0);
         data.push(life);
 
@@ -488,26 +496,14 @@ fn initial_particle_data(num_parts: usize, min_age: f32, max_age: f32) -> Vec<f3
     data
 }
 
-fn random_rg_data(size_x: usize, size_y: usize) -> Vec<u8> {
+fn random_rg_data(rng: &mut SimRng, size_x: usize, size_y: usize) -> Vec<u8> {
     let mut data = vec![];
 
-    let mut rng = rand::thread_rng();
     let die = Uniform::from(0u8..=255);
 
     (0..(size_x * size_y)).for_each(|_| {
-        data.push(die.sample(&mut rng));
-        data.push(die.sample(&mut rng));
-    });
-
-    data
-}
-
-fn fixed_rg_data(size_x: usize, size_y: usize) -> Vec<u8> {
-    let mut data = vec![];
-
-    (0..(size_x * size_y)).for_each(|_| {
-        data.push(0);
-        data.push(0);
+        data.push(die.sample(rng));
+        data.push(die.sample(rng));
     });
 
     data
@@ -0,0 +1,95 @@
+use webgl::mpm::{Boundary, BoundaryType, SimParams, Vec2, Wall};
+
+const FLOOR: Vec2 = Vec2::Y;
+
+fn close(a: Vec2, b: Vec2) -> bool {
+    a.distance(b) < 1e-6
+}
+
+#[test]
+fn sticky_stops_all_motion() {
+    let wall = Boundary::new(BoundaryType::Sticky, 0.0);
+    assert_eq!(wall.project(Vec2::new(3.0, -2.0), FLOOR), Vec2::ZERO);
+    assert_eq!(wall.project(Vec2::new(3.0, 2.0), FLOOR), Vec2::ZERO);
+}
+
+#[test]
+fn slip_keeps_only_the_tangential_motion() {
+    let wall = Boundary::new(BoundaryType::Slip, 0.0);
+    assert!(close(
+        wall.project(Vec2::new(3.0, -2.0), FLOOR),
+        Vec2::new(3.0, 0.0)
+    ));
+    assert!(close(
+        wall.project(Vec2::new(3.0, 2.0), FLOOR),
+        Vec2::new(3.0, 0.0)
+    ));
+}
+
+#[test]
+fn separate_lets_material_leave_the_wall() {
+    let wall = Boundary::new(BoundaryType::Separate, 0.0);
+    assert!(close(
+        wall.project(Vec2::new(3.0, -2.0), FLOOR),
+        Vec2::new(3.0, 0.0)
+    ));
+    assert_eq!(
+        wall.project(Vec2::new(3.0, 2.0), FLOOR),
+        Vec2::new(3.0, 2.0)
+    );
+}
+
+#[test]
+fn open_changes_nothing() {
+    let wall = Boundary::new(BoundaryType::Open, 1.0);
+    assert_eq!(
+        wall.project(Vec2::new(3.0, -2.0), FLOOR),
+        Vec2::new(3.0, -2.0)
+    );
+}
+
+#[test]
+fn friction_slows_sliding_by_the_normal_speed() {
+    let wall = Boundary::new(BoundaryType::Separate, 0.5);
+    assert!(close(
+        wall.project(Vec2::new(3.0, -2.0), FLOOR),
+        Vec2::new(2.0, 0.0)
+    ));
+    assert!(close(
+        wall.project(Vec2::new(-3.0, -2.0), FLOOR),
+        Vec2::new(-2.0, 0.0)
+    ));
+
+    // Friction only stops sliding, it never reverses it
+    let wall = Boundary::new(BoundaryType::Slip, 10.0);
+    assert_eq!(wall.project(Vec2::new(3.0, -2.0), FLOOR), Vec2::ZERO);
+
+    // Separating material feels no friction
+    assert_eq!(
+        Boundary::new(BoundaryType::Separate, 10.0).project(Vec2::new(3.0, 2.0), FLOOR),
+        Vec2::new(3.0, 2.0)
+    );
+}
+
+#[test]
+fn projection_uses_the_wall_normal() {
+    let wall = Boundary::new(BoundaryType::Slip, 0.0);
+    assert!(close(
+        wall.project(Vec2::new(-2.0, 3.0), Wall::Left.normal()),
+        Vec2::new(0.0, 3.0)
+    ));
+    assert!(close(
+        wall.project(Vec2::new(2.0, 3.0), Wall::Right.normal()),
+        Vec2::new(0.0, 3.0)
+    ));
+}
+
+#[test]
+fn floor_friction_is_the_bottom_wall_friction() {
+    let mut params = SimParams::default();
+    params.set_floor_friction(0.7);
+
+    assert_eq!(params.boundary(Wall::Bottom).friction(), 0.7);
+    assert_eq!(params.boundary(Wall::Bottom).kind(), BoundaryType::Separate);
+    assert_eq!(params.boundary(Wall::Top).friction(), 0.0);
+}
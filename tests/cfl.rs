@@ -0,0 +1,75 @@
+use webgl::mpm::{Domain, MaterialParams, MpmSolver, Particle, SimParams, Vec2};
+
+fn solver(params: SimParams) -> (MpmSolver, usize) {
+    let mut solver = MpmSolver::new(Domain::unit_square(64), params, Some(1));
+    let jelly = solver.add_material(MaterialParams::default());
+    solver.add_particles(100, Vec2::new(0.5, 0.5), 0xffffffff, jelly);
+    (solver, jelly)
+}
+
+#[test]
+fn empty_scene_has_no_limit() {
+    let solver = MpmSolver::new(Domain::unit_square(64), SimParams::default(), None);
+    assert_eq!(solver.stable_dt(), f32::INFINITY);
+}
+
+#[test]
+fn stiffer_material_needs_shorter_steps() {
+    let (mut solver, jelly) = solver(SimParams::default());
+    let soft = solver.stable_dt();
+
+    // Wave speed goes with the square root of stiffness
+    let material = solver.material_mut(jelly).unwrap();
+    material.set_youngs_modulus(4.0 * material.youngs_modulus());
+    let stiff = solver.stable_dt();
+
+    assert!(
+        (stiff - 0.5 * soft).abs() < 1e-3 * soft,
+        "{} vs {}",
+        stiff,
+        soft
+    );
+}
+
+#[test]
+fn faster_particles_need_shorter_steps() {
+    let (mut solver, jelly) = solver(SimParams::default());
+    let still = solver.stable_dt();
+
+    let mut particle = Particle::new(Vec2::new(0.3, 0.3), 0xffffffff, jelly);
+    particle.velocity = Vec2::new(0.0, -500.0);
+    solver.add_particle(particle);
+
+    assert!(solver.stable_dt() < 0.5 * still);
+}
+
+#[test]
+fn step_is_clamped_to_the_stable_dt() {
+    let (mut solver, _) = solver(SimParams::default());
+
+    let stable = solver.stable_dt();
+    assert_eq!(solver.step(1.0), stable);
+    assert_eq!(solver.last_dt(), stable);
+
+    // Shorter steps than the limit are taken as asked
+    assert_eq!(solver.step(1e-6), 1e-6);
+}
+
+#[test]
+fn zero_cfl_disables_the_limit() {
+    let mut params = SimParams::default();
+    params.set_cfl(0.0);
+    let (mut solver, _) = solver(params);
+
+    assert_eq!(solver.step(1e-3), 1e-3);
+}
+
+#[test]
+fn smaller_cfl_number_gives_shorter_steps() {
+    let mut params = SimParams::default();
+    let (loose, _) = solver(params);
+    params.set_cfl(0.25);
+    let (tight, _) = solver(params);
+
+    assert!((tight.stable_dt() - 0.5 * loose.stable_dt()).abs() < 1e-9);
+}
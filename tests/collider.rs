@@ -0,0 +1,90 @@
+use webgl::mpm::{Boundary, BoundaryType, Collider, Vec2};
+
+fn slip() -> Boundary {
+    Boundary::new(BoundaryType::Slip, 0.0)
+}
+
+fn assert_sdf(collider: &Collider, position: Vec2, distance: f32, normal: Vec2) {
+    let (actual_distance, actual_normal) = collider.signed_distance(position);
+    assert!(
+        (actual_distance - distance).abs() < 1e-5 && actual_normal.distance(normal) < 1e-5,
+        "at {}: ({}, {}) != ({}, {})",
+        position,
+        actual_distance,
+        actual_normal,
+        distance,
+        normal
+    );
+}
+
+#[test]
+fn circle_distance_and_normal() {
+    let circle = Collider::circle(1.0, 1.0, 0.5, slip());
+    assert_sdf(&circle, Vec2::new(2.0, 1.0), 0.5, Vec2::X);
+    assert_sdf(&circle, Vec2::new(1.0, 0.75), -0.25, -Vec2::Y);
+}
+
+#[test]
+fn box_distance_and_normal() {
+    let rectangle = Collider::rectangle(0.0, 0.0, 1.0, 0.5, slip());
+
+    // Outside a side and a corner
+    assert_sdf(&rectangle, Vec2::new(0.2, 1.0), 0.5, Vec2::Y);
+    assert_sdf(&rectangle, Vec2::new(4.0, 4.5), 5.0, Vec2::new(0.6, 0.8));
+
+    // Inside, the nearest side wins
+    assert_sdf(&rectangle, Vec2::new(0.9, 0.0), -0.1, Vec2::X);
+    assert_sdf(&rectangle, Vec2::new(0.0, -0.4), -0.1, -Vec2::Y);
+}
+
+#[test]
+fn rotated_box_rotates_its_normals() {
+    let mut rectangle = Collider::rectangle(0.0, 0.0, 1.0, 0.5, slip());
+    rectangle.set_pose(0.0, 0.0, std::f32::consts::FRAC_PI_2);
+
+    // The long side now points along y
+    assert_sdf(&rectangle, Vec2::new(0.0, 1.5), 0.5, Vec2::Y);
+    assert_sdf(&rectangle, Vec2::new(0.75, 0.0), 0.25, Vec2::X);
+}
+
+#[test]
+fn plane_distance_and_normal() {
+    let plane = Collider::plane(0.0, 1.0, 0.0, 2.0, slip());
+    assert_sdf(&plane, Vec2::new(5.0, 1.5), 0.5, Vec2::Y);
+    assert_sdf(&plane, Vec2::new(-5.0, 0.0), -1.0, Vec2::Y);
+}
+
+#[test]
+fn capsule_distance_and_normal() {
+    let capsule = Collider::capsule(-1.0, 0.0, 1.0, 0.0, 0.25, slip());
+
+    // Beside the segment, and past its end cap
+    assert_sdf(&capsule, Vec2::new(0.5, 1.0), 0.75, Vec2::Y);
+    assert_sdf(&capsule, Vec2::new(2.0, 0.0), 0.75, Vec2::X);
+    assert_sdf(&capsule, Vec2::new(0.0, -0.125), -0.125, -Vec2::Y);
+}
+
+#[test]
+fn projection_only_acts_inside() {
+    let circle = Collider::circle(0.0, 0.0, 1.0, Boundary::new(BoundaryType::Sticky, 0.0));
+    let velocity = Vec2::new(1.0, -1.0);
+
+    assert_eq!(circle.project(Vec2::new(2.0, 0.0), velocity), velocity);
+    assert_eq!(circle.project(Vec2::new(0.5, 0.0), velocity), Vec2::ZERO);
+}
+
+#[test]
+fn moving_collider_constrains_relative_velocity() {
+    let mut plane = Collider::plane(0.0, 0.0, 0.0, 1.0, slip());
+    plane.set_velocity(0.0, 2.0, 0.0);
+
+    // Material resting below a rising floor is carried up with it, keeping
+    // its sliding motion
+    let projected = plane.project(Vec2::new(0.0, -0.1), Vec2::new(3.0, 0.0));
+    assert!(projected.distance(Vec2::new(3.0, 2.0)) < 1e-6);
+
+    // Material rising faster than the floor is left alone by a separating one
+    plane.set_boundary(Boundary::new(BoundaryType::Separate, 0.0));
+    let velocity = Vec2::new(3.0, 5.0);
+    assert_eq!(plane.project(Vec2::new(0.0, -0.1), velocity), velocity);
+}
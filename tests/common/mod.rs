@@ -0,0 +1,24 @@
+use webgl::mpm::{MpmSolver, Particle, Vec2};
+
+/// Fills the rectangle from `min` to `max` with particles of `material` on a
+/// regular lattice, two per grid cell in each direction, each moving at
+/// `velocity(position)`.
+pub fn fill_lattice(
+    solver: &mut MpmSolver,
+    min: Vec2,
+    max: Vec2,
+    material: usize,
+    velocity: impl Fn(Vec2) -> Vec2,
+) {
+    let spacing = 0.5 * solver.domain().cell_size();
+    let counts = ((max - min) / spacing).round();
+
+    for i in 0..counts.x as usize {
+        for j in 0..counts.y as usize {
+            let position = min + Vec2::new(i as f32 + 0.5, j as f32 + 0.5) * spacing;
+            let mut particle = Particle::new(position, 0xffffffff, material);
+            particle.velocity = velocity(position);
+            solver.add_particle(particle);
+        }
+    }
+}
@@ -0,0 +1,22 @@
+use webgl::mpm::{Domain, Vec2};
+
+#[test]
+fn degenerate_domains_are_rejected() {
+    assert!(Domain::new(0.0, 0.0, 0.0, 10, 10).is_err());
+    assert!(Domain::new(0.0, 0.0, -0.1, 10, 10).is_err());
+    assert!(Domain::new(0.0, 0.0, f32::NAN, 10, 10).is_err());
+    assert!(Domain::new(0.0, 0.0, f32::INFINITY, 10, 10).is_err());
+    assert!(Domain::new(0.0, 0.0, 0.1, 0, 10).is_err());
+    assert!(Domain::new(0.0, 0.0, 0.1, 10, 0).is_err());
+    assert!(Domain::new(f32::NAN, 0.0, 0.1, 10, 10).is_err());
+}
+
+#[test]
+fn rectangular_domain_maps_world_to_grid() {
+    let domain = Domain::new(-1.0, 2.0, 0.5, 8, 4).unwrap();
+
+    assert_eq!(domain.size(), Vec2::new(4.0, 2.0));
+    assert_eq!(domain.to_grid(Vec2::new(0.0, 3.0)), Vec2::new(2.0, 2.0));
+    assert!(domain.contains(Vec2::new(2.9, 3.9)));
+    assert!(!domain.contains(Vec2::new(3.1, 3.0)));
+}
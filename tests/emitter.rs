@@ -0,0 +1,90 @@
+use webgl::mpm::{Domain, Emitter, MaterialParams, MpmSolver, SimParams, Sink, Vec2};
+
+#[test]
+fn emits_at_its_rate_across_steps() {
+    let mut emitter = Emitter::new(0.5, 0.5, 0.0, -1.0, 2.0, 250.0, 0);
+    let mut particles = vec![];
+
+    // Steps that each owe a fraction of a particle still add up
+    for _ in 0..100 {
+        emitter.emit(0.003, &mut particles);
+    }
+    assert_eq!(particles.len(), 75);
+
+    for p in &particles {
+        assert_eq!(p.velocity, Vec2::new(0.0, -2.0));
+        assert!((p.position.x - 0.5).abs() <= 0.5 * emitter.width());
+        assert_eq!(p.position.y, 0.5);
+    }
+}
+
+#[test]
+fn invalid_rates_stop_the_emitter() {
+    for rate in [f32::INFINITY, f32::NAN, -10.0] {
+        let mut emitter = Emitter::new(0.5, 0.5, 0.0, -1.0, 2.0, rate, 0);
+        assert_eq!(emitter.rate(), 0.0);
+
+        emitter.set_rate(100.0);
+        emitter.set_rate(rate);
+        assert_eq!(emitter.rate(), 0.0);
+
+        let mut particles = vec![];
+        emitter.emit(1.0, &mut particles);
+        assert!(particles.is_empty());
+    }
+}
+
+#[test]
+fn long_steps_emit_a_bounded_number_of_particles() {
+    let mut emitter = Emitter::new(0.5, 0.5, 0.0, -1.0, 2.0, 1e6, 0);
+    let mut particles = vec![];
+
+    emitter.emit(10.0, &mut particles);
+    assert_eq!(particles.len(), 1000);
+
+    // Overflowing the owed count does not hang or emit anything wild either
+    emitter.set_rate(f32::MAX);
+    emitter.emit(f32::MAX, &mut particles);
+    assert!(particles.len() <= 2000, "{} particles", particles.len());
+
+    // Nothing owed from the long step carries over
+    particles.clear();
+    emitter.set_rate(0.0);
+    emitter.emit(1.0, &mut particles);
+    assert!(particles.is_empty());
+}
+
+#[test]
+fn sink_removes_only_the_particles_inside_it() {
+    let mut solver = MpmSolver::new(Domain::unit_square(32), SimParams::default(), Some(1));
+    let water = solver.add_material(MaterialParams::water());
+    solver.add_particles(100, Vec2::new(0.3, 0.5), 0xffffffff, water);
+    solver.add_particles(100, Vec2::new(0.7, 0.5), 0xffffffff, water);
+
+    let sink = solver.add_sink(Sink::circle(0.7, 0.5, 0.2));
+    solver.step(1e-4);
+    assert_eq!(solver.particles().len(), 100);
+    assert!(solver.particles().iter().all(|p| p.position.x < 0.5));
+
+    // Nothing more is drained once it is removed
+    solver.remove_sink(sink);
+    solver.add_particles(100, Vec2::new(0.7, 0.5), 0xffffffff, water);
+    solver.step(1e-4);
+    assert_eq!(solver.particles().len(), 200);
+}
+
+#[test]
+fn faucet_fills_the_domain() {
+    let mut solver = MpmSolver::new(Domain::unit_square(32), SimParams::default(), Some(1));
+    let water = solver.add_material(MaterialParams::water());
+    solver.add_emitter(Emitter::new(0.5, 0.8, 0.0, -1.0, 1.0, 20000.0, water));
+
+    let mut time = 0.0;
+    for _ in 0..100 {
+        time += solver.step(1e-4);
+    }
+
+    let expected = 20000.0 * time;
+    let count = solver.particles().len() as f32;
+    assert!((count - expected).abs() <= 1.0, "{} != {}", count, expected);
+}
@@ -0,0 +1,93 @@
+use std::convert::TryInto;
+use webgl::mpm::{Domain, MaterialParams, MpmSolver, SimParams, Vec2};
+
+fn scene() -> MpmSolver {
+    let mut solver = MpmSolver::new(
+        Domain::new(-1.0, 0.0, 0.05, 40, 20).unwrap(),
+        SimParams::default(),
+        Some(5),
+    );
+    let snow = solver.add_material(MaterialParams::default());
+    let water = solver.add_material(MaterialParams::water());
+    solver.add_particles(100, Vec2::new(-0.5, 0.5), 0xffffffff, snow);
+    solver.add_particles(150, Vec2::new(0.5, 0.5), 0xffff8000, water);
+    for _ in 0..10 {
+        solver.step(1e-4);
+    }
+    solver
+}
+
+#[test]
+fn ply_holds_every_particle() {
+    let solver = scene();
+    let bytes = solver.export_ply();
+
+    let end = b"end_header\n";
+    let body = bytes.windows(end.len()).position(|w| w == end).unwrap() + end.len();
+    let header = std::str::from_utf8(&bytes[..body]).unwrap();
+    assert!(header.contains("format binary_little_endian 1.0"));
+    assert!(header.contains("element vertex 250"));
+
+    // Eight four byte properties per vertex
+    let records: Vec<&[u8]> = bytes[body..].chunks(32).collect();
+    assert_eq!(records.len(), 250);
+
+    let float =
+        |record: &[u8], i: usize| f32::from_le_bytes(record[4 * i..4 * i + 4].try_into().unwrap());
+    for (record, p) in records.iter().zip(solver.particles()) {
+        assert_eq!(float(record, 0), p.position.x);
+        assert_eq!(float(record, 1), p.position.y);
+        assert_eq!(float(record, 3), p.velocity.x);
+        assert_eq!(float(record, 5), p.deformation_gradient.determinant());
+        assert_eq!(
+            i32::from_le_bytes(record[28..32].try_into().unwrap()),
+            p.material as i32
+        );
+    }
+}
+
+#[test]
+fn vtk_has_a_value_per_particle() {
+    let solver = scene();
+    let vtk = solver.export_vtk();
+    let lines: Vec<&str> = vtk.lines().collect();
+
+    assert_eq!(lines[0], "# vtk DataFile Version 3.0");
+    assert!(lines.contains(&"POINTS 250 float"));
+    assert!(lines.contains(&"POINT_DATA 250"));
+
+    let material = lines
+        .iter()
+        .position(|l| *l == "SCALARS material int 1")
+        .unwrap();
+    let ids = &lines[material + 2..];
+    assert_eq!(ids.len(), 250);
+    assert_eq!(ids.iter().filter(|id| **id == "1").count(), 150);
+}
+
+#[test]
+fn grid_mass_matches_particle_mass() {
+    let solver = scene();
+    let vtk = solver.export_grid_vtk();
+    let lines: Vec<&str> = vtk.lines().collect();
+
+    assert!(lines.contains(&"DIMENSIONS 41 21 1"));
+    assert!(lines.contains(&"ORIGIN -1 0 0"));
+
+    let start = lines
+        .iter()
+        .position(|l| *l == "SCALARS mass float 1")
+        .unwrap()
+        + 2;
+    let grid_mass: f32 = lines[start..start + 41 * 21]
+        .iter()
+        .map(|l| l.parse::<f32>().unwrap())
+        .sum();
+    let particle_mass: f32 = solver
+        .particles()
+        .iter()
+        .map(|p| solver.material(p.material).unwrap().particle_mass())
+        .sum();
+
+    assert!((grid_mass - particle_mass).abs() < 1e-3 * particle_mass);
+}
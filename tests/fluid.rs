@@ -0,0 +1,61 @@
+mod common;
+
+use common::fill_lattice;
+use webgl::mpm::{Domain, MaterialParams, MpmSolver, SimParams, Vec2};
+
+const GRID_SIZE: usize = 64;
+const DT: f32 = 1e-4;
+
+/// Releases a column of water `width` by `height` cells standing on the floor.
+fn release_column(width: usize, height: usize, steps: usize) -> MpmSolver {
+    let params = SimParams::default();
+    let mut solver = MpmSolver::new(Domain::unit_square(GRID_SIZE), params, None);
+    let water = solver.add_material(MaterialParams::water());
+
+    let size = Vec2::new(width as f32, height as f32) / GRID_SIZE as f32;
+    let corner = Vec2::new(0.5 - 0.5 * size.x, params.boundary_thickness());
+    fill_lattice(&mut solver, corner, corner + size, water, |_| Vec2::ZERO);
+
+    for _ in 0..steps {
+        solver.step(DT);
+    }
+
+    solver
+}
+
+/// Width of the water, and the mean height of its particles above the floor.
+fn extent(solver: &MpmSolver) -> (f32, f32) {
+    let floor = solver.params().boundary_thickness();
+    let particles = solver.particles();
+    let (min_x, max_x) = particles
+        .iter()
+        .fold((f32::INFINITY, f32::NEG_INFINITY), |(min_x, max_x), p| {
+            (min_x.min(p.position.x), max_x.max(p.position.x))
+        });
+    let height =
+        particles.iter().map(|p| p.position.y - floor).sum::<f32>() / particles.len() as f32;
+    (max_x - min_x, height)
+}
+
+#[test]
+fn column_spreads_into_a_flat_layer() {
+    let (width, height) = (8, 24);
+    let (_, start_depth) = extent(&release_column(width, height, 0));
+    let solver = release_column(width, height, 4000);
+    let (spread, depth) = extent(&solver);
+
+    // Water keeps no shape of its own, unlike the sand pile, and covers the
+    // floor from wall to wall
+    assert!(spread > 0.8, "did not spread: {}", spread);
+    assert!(depth < 0.5 * start_depth, "did not flatten: {}", depth);
+}
+
+#[test]
+fn water_stays_nearly_incompressible() {
+    let solver = release_column(8, 24, 4000);
+
+    for p in solver.particles() {
+        let j = p.deformation_gradient.determinant();
+        assert!(j > 0.8 && j < 1.2, "volume ratio was {}", j);
+    }
+}
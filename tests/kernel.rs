@@ -0,0 +1,121 @@
+use glam::IVec2;
+use webgl::mpm::{Domain, Kernel, MaterialParams, MpmSolver, Particle, SimParams, Vec2};
+
+const KERNELS: [Kernel; 3] = [Kernel::Linear, Kernel::Quadratic, Kernel::Cubic];
+
+/// Particle positions, in grid cells, spread over a few cells including
+/// exactly on and just either side of a node.
+fn positions() -> impl Iterator<Item = Vec2> {
+    (0..=40).flat_map(|i| {
+        (0..=12).map(move |j| Vec2::new(4.0 + 0.025 * i as f32, 7.0 + 0.0833 * j as f32))
+    })
+}
+
+#[test]
+fn weights_partition_unity() {
+    for kernel in KERNELS {
+        for position in positions() {
+            let sum: f32 = kernel.stencil(position).nodes(1.0).map(|n| n.weight).sum();
+            assert!(
+                (sum - 1.0).abs() < 1e-5,
+                "{:?} at {}: {}",
+                kernel,
+                position,
+                sum
+            );
+        }
+    }
+}
+
+#[test]
+fn weights_have_zero_first_moment() {
+    for kernel in KERNELS {
+        for position in positions() {
+            let moment = kernel
+                .stencil(position)
+                .nodes(1.0)
+                .fold(Vec2::ZERO, |sum, n| sum + n.weight * n.dpos);
+            assert!(
+                moment.length() < 1e-5,
+                "{:?} at {}: {}",
+                kernel,
+                position,
+                moment
+            );
+        }
+    }
+}
+
+#[test]
+fn gradients_sum_to_zero() {
+    for kernel in KERNELS {
+        for position in positions() {
+            let sum = kernel
+                .stencil(position)
+                .nodes(1.0)
+                .fold(Vec2::ZERO, |sum, n| sum + n.gradient);
+            assert!(sum.length() < 1e-5, "{:?} at {}: {}", kernel, position, sum);
+        }
+    }
+}
+
+#[test]
+fn wide_stencils_near_the_walls_stay_on_coarse_grids() {
+    for kernel in KERNELS {
+        let mut params = SimParams::new(-400.0, -400.0, 0.1);
+        params.set_kernel(kernel);
+        let mut solver = MpmSolver::new(Domain::unit_square(8), params, Some(1));
+        let snow = solver.add_material(MaterialParams::default());
+        solver.add_particles(100, Vec2::new(0.15, 0.15), 0xffffffff, snow);
+
+        // Thrown into the corner, the stencils must not reach off the grid
+        for _ in 0..200 {
+            solver.step(1e-3);
+        }
+        assert!(solver
+            .particles()
+            .iter()
+            .all(|p| solver.domain().contains(p.position)));
+    }
+}
+
+#[test]
+fn far_away_positions_lie_outside_the_grid() {
+    let nodes = IVec2::splat(9);
+    for kernel in KERNELS {
+        assert!(kernel.stencil_lies_within(Vec2::splat(4.0), nodes));
+        for position in [
+            Vec2::new(-0.1, 4.0),
+            Vec2::new(4.0, 9.0),
+            Vec2::new(1e30, 4.0),
+            Vec2::new(4.0, -1e30),
+            Vec2::new(f32::INFINITY, 4.0),
+            Vec2::new(4.0, f32::NAN),
+        ] {
+            assert!(
+                !kernel.stencil_lies_within(position, nodes),
+                "{:?} at {}",
+                kernel,
+                position
+            );
+        }
+    }
+}
+
+#[test]
+fn particles_flung_off_the_grid_are_removed() {
+    for kernel in KERNELS {
+        let mut params = SimParams::default();
+        params.set_kernel(kernel);
+        let mut solver = MpmSolver::new(Domain::unit_square(8), params, Some(1));
+        let snow = solver.add_material(MaterialParams::default());
+        solver.add_particles(10, Vec2::new(0.5, 0.5), 0xffffffff, snow);
+
+        solver.add_particle(Particle::new(Vec2::new(1e30, 0.5), 0xffffffff, snow));
+        solver.add_particle(Particle::new(Vec2::splat(f32::INFINITY), 0xffffffff, snow));
+        solver.add_particle(Particle::new(Vec2::new(0.5, f32::NAN), 0xffffffff, snow));
+
+        solver.step(1e-4);
+        assert_eq!(solver.particles().len(), 10);
+    }
+}
@@ -0,0 +1,36 @@
+use webgl::mpm::{MaterialParams, PlasticityModel};
+
+fn lame(youngs_modulus: f32, poisson_ratio: f32) -> (f32, f32) {
+    let mu = youngs_modulus / (2.0 * (1.0 + poisson_ratio));
+    let lambda =
+        youngs_modulus * poisson_ratio / ((1.0 + poisson_ratio) * (1.0 - 2.0 * poisson_ratio));
+    (mu, lambda)
+}
+
+fn assert_lame(material: &MaterialParams) {
+    let (mu, lambda) = lame(material.youngs_modulus(), material.poisson_ratio());
+    assert!(
+        (material.mu_0() - mu).abs() <= 1e-3 * mu,
+        "mu {}",
+        material.mu_0()
+    );
+    assert!(
+        (material.lambda_0() - lambda).abs() <= 1e-3 * lambda,
+        "lambda {}",
+        material.lambda_0()
+    );
+}
+
+#[test]
+fn lame_parameters_follow_youngs_modulus_and_poisson_ratio() {
+    let mut material = MaterialParams::new(1.0, 1.0, 10.0, 10000.0, 0.2, PlasticityModel::None);
+    assert_lame(&material);
+
+    material.set_youngs_modulus(40000.0);
+    assert_lame(&material);
+    assert!((material.mu_0() - 40000.0 / 2.4).abs() < 1e-2);
+
+    material.set_poisson_ratio(0.35);
+    assert_lame(&material);
+    assert!((material.mu_0() - 40000.0 / 2.7).abs() < 1e-2);
+}
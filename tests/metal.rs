@@ -0,0 +1,78 @@
+mod common;
+
+use common::fill_lattice;
+use webgl::mpm::{Domain, MaterialParams, MpmSolver, PlasticityModel, SimParams, Vec2};
+
+const GRID_SIZE: usize = 64;
+const DT: f32 = 1e-4;
+const BAR_LENGTH: usize = 16;
+const BAR_THICKNESS: usize = 4;
+
+/// Pulls the ends of a floating bar apart and lets it ring out, returning
+/// its final length.
+fn stretch_bar(material: MaterialParams) -> (MpmSolver, f32) {
+    let mut solver = MpmSolver::new(
+        Domain::unit_square(GRID_SIZE),
+        SimParams::new(0.0, 0.0, 0.05),
+        None,
+    );
+    let metal = solver.add_material(material);
+
+    // Moving apart from the middle
+    let size = Vec2::new(BAR_LENGTH as f32, BAR_THICKNESS as f32) / GRID_SIZE as f32;
+    let corner = Vec2::splat(0.5) - 0.5 * size;
+    fill_lattice(&mut solver, corner, corner + size, metal, |position| {
+        Vec2::new(100.0 * (position.x - 0.5), 0.0)
+    });
+
+    for _ in 0..3000 {
+        solver.step(DT);
+    }
+
+    let (min_x, max_x) = solver
+        .particles()
+        .iter()
+        .fold((f32::INFINITY, f32::NEG_INFINITY), |(min_x, max_x), p| {
+            (min_x.min(p.position.x), max_x.max(p.position.x))
+        });
+    (solver, max_x - min_x)
+}
+
+fn plastic_strain(solver: &MpmSolver) -> f32 {
+    solver
+        .particles()
+        .iter()
+        .map(|p| p.plastic_strain)
+        .fold(0.0, f32::max)
+}
+
+#[test]
+fn stretched_bar_yields_and_stays_longer() {
+    let (elastic, elastic_length) = stretch_bar(MaterialParams::new(
+        1.0,
+        1.0,
+        0.0,
+        10000.0,
+        0.3,
+        PlasticityModel::None,
+    ));
+    let (metal, metal_length) = stretch_bar(MaterialParams::metal());
+
+    assert_eq!(plastic_strain(&elastic), 0.0);
+    assert!(plastic_strain(&metal) > 0.0);
+    assert!(
+        metal_length > elastic_length + 0.02,
+        "metal {} elastic {}",
+        metal_length,
+        elastic_length
+    );
+}
+
+#[test]
+fn bar_below_the_yield_stress_springs_back() {
+    let mut material = MaterialParams::metal();
+    material.set_yield_stress(1e6);
+    let (solver, _) = stretch_bar(material);
+
+    assert_eq!(plastic_strain(&solver), 0.0);
+}
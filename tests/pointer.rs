@@ -0,0 +1,28 @@
+use webgl::mpm::{Pointer, PointerMode, Vec2};
+
+#[test]
+fn pointer_glides_only_as_long_as_it_was_told() {
+    let mut pointer = Pointer::new(0.5, 0.5, 0.1, PointerMode::Grab, 0.0);
+    pointer.set_velocity(1.0, 0.0, 0.01);
+
+    pointer.advance(0.004);
+    assert!(pointer.position().distance(Vec2::new(0.504, 0.5)) < 1e-6);
+    assert_eq!(pointer.velocity(), Vec2::new(1.0, 0.0));
+
+    // The rest of the glide, then nothing until the next event
+    for _ in 0..100 {
+        pointer.advance(0.004);
+    }
+    assert!(pointer.position().distance(Vec2::new(0.51, 0.5)) < 1e-6);
+    assert_eq!(pointer.velocity(), Vec2::ZERO);
+}
+
+#[test]
+fn stopped_grab_holds_material_still() {
+    let mut pointer = Pointer::new(0.5, 0.5, 0.1, PointerMode::Grab, 0.0);
+    pointer.set_velocity(1.0, 0.0, 0.01);
+    pointer.advance(0.02);
+
+    let held = pointer.apply(pointer.position(), Vec2::new(0.0, -2.0), 1e-4);
+    assert_eq!(held, Vec2::ZERO);
+}
@@ -0,0 +1,164 @@
+use webgl::mpm::{Domain, Emitter, MaterialParams, MpmSolver, Playback, SimParams, Sink, Vec2};
+
+/// Runs a scene, with a faucet and drain that change the particle count if
+/// `flowing`, recording every other step. Returns the recording with the
+/// positions it should contain.
+fn record(quantized: bool, flowing: bool) -> (Vec<u8>, Vec<Vec<Vec2>>) {
+    let mut solver = MpmSolver::new(Domain::unit_square(32), SimParams::default(), Some(3));
+    let water = solver.add_material(MaterialParams::water());
+    solver.add_particles(300, Vec2::new(0.5, 0.5), 0xffff8000, water);
+    if flowing {
+        solver.add_emitter(Emitter::new(0.2, 0.8, 1.0, 0.0, 1.0, 20000.0, water));
+        solver.add_sink(Sink::rectangle(0.0, 0.0, 1.0, 0.1));
+    }
+
+    solver.start_recording(quantized, 2);
+    let mut expected = vec![];
+    for step in 1..=300 {
+        solver.step(1e-4);
+        if step % 2 == 0 {
+            expected.push(solver.particles().iter().map(|p| p.position).collect());
+        }
+    }
+
+    (solver.stop_recording().unwrap(), expected)
+}
+
+fn assert_frame(playback: &Playback, expected: &[Vec2], tolerance: f32) {
+    assert_eq!(playback.positions().len(), expected.len());
+    for (actual, expected) in playback.positions().iter().zip(expected) {
+        assert!(
+            actual.distance(*expected) <= tolerance,
+            "frame {}: {} != {}",
+            playback.frame(),
+            actual,
+            expected
+        );
+    }
+}
+
+#[test]
+fn playback_reproduces_every_frame() {
+    for (quantized, flowing, tolerance) in [
+        (false, false, 0.0),
+        (false, true, 0.0),
+        (true, false, 1e-4),
+        (true, true, 1e-4),
+    ] {
+        let (bytes, expected) = record(quantized, flowing);
+        let mut playback = Playback::new(bytes).unwrap();
+        assert_eq!(playback.frame_count(), expected.len());
+
+        for (i, frame) in expected.iter().enumerate() {
+            playback.seek(i);
+            assert_frame(&playback, frame, tolerance);
+        }
+
+        // Seeking backwards restarts from an earlier keyframe
+        playback.seek(97);
+        assert_frame(&playback, &expected[97], tolerance);
+    }
+}
+
+#[test]
+fn quantized_recordings_are_smaller() {
+    // Mostly one-byte deltas against eight bytes of floats per particle
+    assert!(record(true, false).0.len() * 3 < record(false, false).0.len());
+}
+
+/// Stream header for a recording of the unit square.
+fn header(quantized: bool) -> Vec<u8> {
+    let mut bytes = b"MPMR".to_vec();
+    bytes.extend_from_slice(&1u32.to_le_bytes());
+    bytes.push(quantized as u8);
+    for v in [0.0f32, 0.0, 1.0, 1.0] {
+        bytes.extend_from_slice(&v.to_le_bytes());
+    }
+    bytes
+}
+
+/// Frame header with `flags` 1 for a keyframe plus 2 if it has colours.
+fn frame(bytes: &mut Vec<u8>, flags: u8, count: u64) {
+    bytes.extend_from_slice(&1e-4f32.to_le_bytes());
+    bytes.push(flags);
+    let mut v = count;
+    while v >= 0x80 {
+        bytes.push(v as u8 | 0x80);
+        v >>= 7;
+    }
+    bytes.push(v as u8);
+}
+
+/// A quantized keyframe of two particles with colours.
+fn keyframe_of_two() -> Vec<u8> {
+    let mut bytes = header(true);
+    frame(&mut bytes, 3, 2);
+    bytes.extend_from_slice(&[0; 4 * 2]);
+    bytes.extend_from_slice(&[0xff; 4 * 2]);
+    bytes
+}
+
+#[test]
+fn malformed_recordings_are_rejected() {
+    // Counts so large their byte lengths overflow
+    for count in [u64::MAX, u64::MAX / 4, 1 << 62] {
+        for quantized in [false, true] {
+            let mut bytes = header(quantized);
+            frame(&mut bytes, 3, count);
+            bytes.extend_from_slice(&[0; 16]);
+            assert!(Playback::new(bytes).is_err(), "count {}", count);
+        }
+    }
+
+    // A delta frame must move the particles of its keyframe
+    let mut bytes = keyframe_of_two();
+    frame(&mut bytes, 0, 3);
+    bytes.extend_from_slice(&[0; 2 * 3]);
+    assert!(Playback::new(bytes).is_err());
+
+    // A frame without colours reuses ones for as many particles
+    let mut bytes = keyframe_of_two();
+    frame(&mut bytes, 1, 3);
+    bytes.extend_from_slice(&[0; 4 * 3]);
+    assert!(Playback::new(bytes).is_err());
+
+    // Well-formed, to show the cases above fail for the right reason
+    let mut bytes = keyframe_of_two();
+    frame(&mut bytes, 0, 2);
+    bytes.extend_from_slice(&[0; 2 * 2]);
+    let playback = Playback::new(bytes).unwrap();
+    assert_eq!(playback.frame_count(), 2);
+    assert_eq!(playback.colours(), &[u32::MAX, u32::MAX]);
+}
+
+#[test]
+fn damaged_recordings_never_panic() {
+    let mut solver = MpmSolver::new(Domain::unit_square(16), SimParams::default(), Some(3));
+    let water = solver.add_material(MaterialParams::water());
+    solver.add_particles(8, Vec2::new(0.5, 0.5), 0xffff8000, water);
+    solver.add_emitter(Emitter::new(0.5, 0.8, 0.0, -1.0, 1.0, 20000.0, water));
+    solver.start_recording(true, 1);
+    for _ in 0..6 {
+        solver.step(1e-4);
+    }
+    let bytes = solver.stop_recording().unwrap();
+
+    let play_all = |bytes: Vec<u8>| {
+        if let Ok(mut playback) = Playback::new(bytes) {
+            for i in 0..playback.frame_count() {
+                playback.seek(i);
+            }
+        }
+    };
+
+    for len in 0..bytes.len() {
+        play_all(bytes[..len].to_vec());
+    }
+    for i in 0..bytes.len() {
+        for value in [0, 0x7f, 0x80, 0xff] {
+            let mut damaged = bytes.clone();
+            damaged[i] = value;
+            play_all(damaged);
+        }
+    }
+}
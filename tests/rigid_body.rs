@@ -0,0 +1,133 @@
+mod common;
+
+use common::fill_lattice;
+use webgl::mpm::{
+    Boundary, BoundaryType, Collider, Domain, MaterialParams, MpmSolver, RigidBody, SimParams, Vec2,
+};
+
+const GRID_SIZE: usize = 32;
+const DT: f32 = 1e-4;
+
+fn slip() -> Boundary {
+    Boundary::new(BoundaryType::Slip, 0.0)
+}
+
+fn particle_momentum(solver: &MpmSolver) -> Vec2 {
+    solver.particles().iter().fold(Vec2::ZERO, |sum, p| {
+        sum + solver.material(p.material).unwrap().particle_mass() * p.velocity
+    })
+}
+
+#[test]
+fn invalid_mass_is_rejected() {
+    let circle = Collider::circle(0.5, 0.5, 0.1, slip());
+    assert!(RigidBody::new(circle, 0.0, 1.0).is_err());
+    assert!(RigidBody::new(circle, -1.0, 1.0).is_err());
+    assert!(RigidBody::new(circle, f32::NAN, 1.0).is_err());
+    assert!(RigidBody::new(circle, 1.0, 0.0).is_err());
+    assert!(RigidBody::new(circle, 1.0, f32::NAN).is_err());
+    assert!(RigidBody::new(circle, f32::INFINITY, f32::INFINITY).is_ok());
+    assert!(RigidBody::from_density(circle, 0.0).is_err());
+}
+
+#[test]
+fn material_and_body_exchange_momentum() {
+    let mut solver = MpmSolver::new(
+        Domain::unit_square(GRID_SIZE),
+        SimParams::new(0.0, 0.0, 0.05),
+        None,
+    );
+    let water = solver.add_material(MaterialParams::water());
+
+    // A slab of water thrown at a resting disc
+    fill_lattice(
+        &mut solver,
+        Vec2::new(0.2, 0.4),
+        Vec2::new(0.35, 0.6),
+        water,
+        |_| Vec2::new(2.0, 0.0),
+    );
+    let body = RigidBody::new(Collider::circle(0.5, 0.5, 0.08, slip()), 100.0, 1.0).unwrap();
+    let id = solver.add_rigid_body(body);
+
+    let before = particle_momentum(&solver);
+    for _ in 0..1000 {
+        solver.step(DT);
+    }
+
+    let body = solver.rigid_body(id).unwrap();
+    let body_momentum = body.mass() * body.collider().velocity();
+    let after = particle_momentum(&solver) + body_momentum;
+
+    // The body was pushed along, and nothing was lost to it
+    assert!(
+        body_momentum.x > 0.1 * before.x,
+        "body got {}",
+        body_momentum
+    );
+    assert!(
+        after.distance(before) < 0.02 * before.length(),
+        "{} != {}",
+        after,
+        before
+    );
+}
+
+#[test]
+fn infinite_mass_body_stays_put() {
+    let mut solver = MpmSolver::new(Domain::unit_square(GRID_SIZE), SimParams::default(), None);
+    let snow = solver.add_material(MaterialParams::default());
+    solver.add_particles(200, Vec2::new(0.5, 0.7), 0xffffffff, snow);
+
+    let collider = Collider::rectangle(0.5, 0.4, 0.2, 0.05, slip());
+    let id = solver.add_rigid_body(RigidBody::new(collider, f32::INFINITY, f32::INFINITY).unwrap());
+
+    for _ in 0..1000 {
+        solver.step(DT);
+    }
+
+    let body = solver.rigid_body(id).unwrap().collider();
+    assert_eq!(body.position(), Vec2::new(0.5, 0.4));
+    assert_eq!(body.velocity(), Vec2::ZERO);
+}
+
+/// Drops a box of `relative_density` times the water's into a pool,
+/// returning how far its center ends up above the floor.
+fn drop_box_in_water(relative_density: f32) -> f32 {
+    let params = SimParams::default();
+    let floor = params.boundary_thickness();
+    let mut solver = MpmSolver::new(Domain::unit_square(GRID_SIZE), params, None);
+    let water = solver.add_material(MaterialParams::water());
+
+    let depth = 0.3;
+    fill_lattice(
+        &mut solver,
+        Vec2::new(floor, floor),
+        Vec2::new(1.0 - floor, floor + depth),
+        water,
+        |_| Vec2::ZERO,
+    );
+
+    // Four particles of unit mass per cell
+    let water_density = 4.0 * (GRID_SIZE * GRID_SIZE) as f32;
+    let collider = Collider::rectangle(0.5, floor + depth, 0.08, 0.08, slip());
+    let id = solver.add_rigid_body(
+        RigidBody::from_density(collider, relative_density * water_density).unwrap(),
+    );
+
+    for _ in 0..2500 {
+        solver.step(DT);
+    }
+
+    solver.rigid_body(id).unwrap().collider().position().y - floor
+}
+
+#[test]
+fn light_box_floats_and_heavy_box_sinks() {
+    let light = drop_box_in_water(0.3);
+    let heavy = drop_box_in_water(3.0);
+
+    // The heavy box rests on the floor, half its 0.16 height above it
+    assert!(light > 0.2, "light box sank to {}", light);
+    assert!(heavy < 0.1, "heavy box floated at {}", heavy);
+}
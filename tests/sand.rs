@@ -0,0 +1,84 @@
+mod common;
+
+use common::fill_lattice;
+use webgl::mpm::{Domain, MaterialParams, MpmSolver, SimParams, Vec2};
+
+const GRID_SIZE: usize = 64;
+const DT: f32 = 1e-4;
+const COLUMN_CELLS: usize = 10;
+
+/// Drops a square column of sand onto a rough floor and lets it settle.
+fn collapse_column(friction_angle: f32) -> MpmSolver {
+    let mut params = SimParams::default();
+    params.set_floor_friction(1.0);
+
+    let mut solver = MpmSolver::new(Domain::unit_square(GRID_SIZE), params, None);
+
+    let mut sand = MaterialParams::sand();
+    sand.set_friction_angle(friction_angle);
+    let sand = solver.add_material(sand);
+
+    // Resting on the floor
+    let size = COLUMN_CELLS as f32 / GRID_SIZE as f32;
+    let corner = Vec2::new(0.5 - 0.5 * size, params.boundary_thickness());
+    fill_lattice(
+        &mut solver,
+        corner,
+        corner + Vec2::splat(size),
+        sand,
+        |_| Vec2::ZERO,
+    );
+
+    for _ in 0..2500 {
+        solver.step(DT);
+    }
+
+    solver
+}
+
+/// Half-width of the pile at its base and its height above the floor.
+fn pile_extent(solver: &MpmSolver) -> (f32, f32) {
+    let floor = solver.params().boundary_thickness();
+    solver
+        .particles()
+        .iter()
+        .fold((0.0f32, 0.0f32), |(half_width, height), p| {
+            (
+                half_width.max((p.position.x - 0.5).abs()),
+                height.max(p.position.y - floor),
+            )
+        })
+}
+
+fn slope_angle(solver: &MpmSolver) -> f32 {
+    let (half_width, height) = pile_extent(solver);
+    (height / half_width).atan().to_degrees()
+}
+
+#[test]
+fn column_collapses_into_a_resting_pile() {
+    let solver = collapse_column(35.0);
+    let (half_width, height) = pile_extent(&solver);
+    let column_size = COLUMN_CELLS as f32 / GRID_SIZE as f32;
+
+    assert!(solver.particles().iter().all(|p| p.velocity.length() < 0.2));
+    assert!(
+        half_width > 0.75 * column_size,
+        "did not spread: {}",
+        half_width
+    );
+    assert!(height < 0.9 * column_size, "did not collapse: {}", height);
+
+    // The pile is not a flat puddle, but is no steeper than the friction angle
+    let angle = slope_angle(&solver);
+    assert!(angle > 10.0 && angle < 35.0, "slope was {} degrees", angle);
+}
+
+#[test]
+fn higher_friction_angle_gives_steeper_pile() {
+    let loose = collapse_column(25.0);
+    let steep = collapse_column(45.0);
+
+    assert!(slope_angle(&steep) > slope_angle(&loose) + 5.0);
+    assert!(pile_extent(&steep).0 < pile_extent(&loose).0);
+}
@@ -0,0 +1,141 @@
+use webgl::mpm::{
+    Domain, MaterialParams, MpmSolver, Sampling, SeedImage, SeedOptions, SeedShape, SimParams,
+};
+
+const DENSITY: f32 = 20000.0;
+
+fn shapes() -> Vec<SeedShape> {
+    vec![
+        SeedShape::rectangle(0.2, 0.3, 0.6, 0.5),
+        SeedShape::disk(0.5, 0.5, 0.2),
+        SeedShape::ring(0.5, 0.5, 0.1, 0.25),
+        // An L, which is not convex
+        SeedShape::polygon(&[0.2, 0.2, 0.6, 0.2, 0.6, 0.3, 0.3, 0.3, 0.3, 0.6, 0.2, 0.6]),
+    ]
+}
+
+fn solver() -> (MpmSolver, usize) {
+    let mut solver = MpmSolver::new(Domain::unit_square(64), SimParams::default(), Some(5));
+    let snow = solver.add_material(MaterialParams::default());
+    (solver, snow)
+}
+
+#[test]
+fn seeds_area_times_density_inside_the_shape() {
+    for sampling in [Sampling::Random, Sampling::Jittered] {
+        for shape in shapes() {
+            let (mut solver, snow) = solver();
+            let mut options = SeedOptions::new(snow, DENSITY);
+            options.set_sampling(sampling);
+
+            let count = solver.seed(&shape, &options);
+            let expected = shape.area() * DENSITY;
+            assert_eq!(count, solver.particles().len());
+            assert!(
+                (count as f32 - expected).abs() < 0.05 * expected,
+                "{:?} {:?}: {} != {}",
+                sampling,
+                shape,
+                count,
+                expected
+            );
+            assert!(solver
+                .particles()
+                .iter()
+                .all(|p| shape.contains(p.position)));
+        }
+    }
+}
+
+#[test]
+fn invalid_densities_seed_nothing() {
+    for sampling in [Sampling::Random, Sampling::Jittered] {
+        for density in [0.0, -DENSITY, f32::NAN, f32::INFINITY] {
+            let (mut solver, snow) = solver();
+            let mut options = SeedOptions::new(snow, density);
+            options.set_sampling(sampling);
+
+            assert_eq!(solver.seed(&shapes()[0], &options), 0);
+            assert!(solver.particles().is_empty());
+        }
+    }
+}
+
+#[test]
+fn huge_densities_seed_a_bounded_number_of_particles() {
+    let shapes = [
+        SeedShape::rectangle(0.2, 0.3, 0.6, 0.5),
+        // A sliver, whose ceiled cells would otherwise dwarf its area
+        SeedShape::rectangle(0.5, 0.0, 0.5 + 1e-6, 1.0),
+    ];
+    for sampling in [Sampling::Random, Sampling::Jittered] {
+        for shape in &shapes {
+            let (mut solver, snow) = solver();
+            let mut options = SeedOptions::new(snow, 1e30);
+            options.set_sampling(sampling);
+
+            let count = solver.seed(shape, &options);
+            assert!(count <= 1 << 21, "{:?}: {}", sampling, count);
+        }
+    }
+}
+
+#[test]
+fn shape_areas_are_exact() {
+    use std::f32::consts::PI;
+
+    let areas = [0.08, PI * 0.04, PI * (0.0625 - 0.01), 0.07];
+    for (shape, area) in shapes().iter().zip(areas) {
+        assert!((shape.area() - area).abs() < 1e-6, "{:?}", shape);
+    }
+}
+
+/// Two by two image: red over green on the left, blue and a transparent
+/// pixel on the right, rows given from the top.
+fn image() -> SeedImage {
+    #[rustfmt::skip]
+    let pixels = vec![
+        255, 0, 0, 255,   0, 0, 255, 255,
+        0, 255, 0, 255,   0, 0, 0, 0,
+    ];
+    let mut image = SeedImage::new(pixels, 2, 2);
+    image.place(0.2, 0.2, 0.4);
+    image
+}
+
+#[test]
+fn image_pixels_become_particles_of_their_colour() {
+    const RED: u32 = 0xff0000ff;
+    const GREEN: u32 = 0xff00ff00;
+    const BLUE: u32 = 0xffff0000;
+
+    let (mut solver, snow) = solver();
+    let count = solver.seed_image(&image(), &SeedOptions::new(snow, DENSITY));
+
+    // Three opaque pixels of 0.2 by 0.2
+    let expected = 3.0 * 0.04 * DENSITY;
+    assert!((count as f32 - expected).abs() < 0.05 * expected);
+
+    for p in solver.particles() {
+        let right = p.position.x >= 0.4;
+        let top = p.position.y >= 0.4;
+        let colour = match (right, top) {
+            (false, true) => RED,
+            (false, false) => GREEN,
+            (true, true) => BLUE,
+            (true, false) => panic!("seeded the transparent pixel at {}", p.position),
+        };
+        assert_eq!(p.colour, colour, "at {}", p.position);
+    }
+}
+
+#[test]
+fn alpha_threshold_hides_faint_pixels() {
+    let mut image = image();
+    image.set_alpha_threshold(0);
+
+    let (mut solver, snow) = solver();
+    let count = solver.seed_image(&image, &SeedOptions::new(snow, DENSITY));
+
+    assert!(count as f32 > 3.9 * 0.04 * DENSITY);
+}
@@ -0,0 +1,67 @@
+use webgl::mpm::{Domain, MaterialParams, MpmSolver, SimParams, Vec2};
+
+/// Drops a blob of the default material and steps it natively.
+fn drop_blob(steps: usize) -> MpmSolver {
+    let mut solver = MpmSolver::new(Domain::unit_square(32), SimParams::default(), Some(1));
+    let snow = solver.add_material(MaterialParams::default());
+    solver.add_particles(300, Vec2::new(0.5, 0.6), 0xffffffff, snow);
+
+    for _ in 0..steps {
+        solver.step(1e-4);
+    }
+
+    solver
+}
+
+fn mean_height(solver: &MpmSolver) -> f32 {
+    let particles = solver.particles();
+    particles.iter().map(|p| p.position.y).sum::<f32>() / particles.len() as f32
+}
+
+#[test]
+fn blob_falls_under_gravity() {
+    let start = mean_height(&drop_blob(0));
+    let solver = drop_blob(200);
+
+    assert_eq!(solver.particles().len(), 300);
+    assert!(mean_height(&solver) < start - 0.01);
+    assert!(solver
+        .particles()
+        .iter()
+        .all(|p| p.velocity.y < 0.0 && p.velocity.is_finite()));
+}
+
+#[test]
+fn blob_lands_on_the_floor() {
+    let solver = drop_blob(5000);
+    let floor = solver.params().boundary_thickness();
+
+    assert!(mean_height(&solver) < 0.25, "did not land");
+    for p in solver.particles() {
+        assert!(solver.domain().contains(p.position));
+        assert!(p.position.y > floor - 0.02, "sank to {}", p.position.y);
+    }
+}
+
+#[test]
+fn update_splits_substeps_to_meet_the_cfl_limit() {
+    let mut params = SimParams::new(0.0, 0.0, 0.05);
+    params.set_substep_dt(1e-3);
+    params.set_max_substeps(1000);
+
+    // Nothing moves without gravity, so the limit holds still
+    let mut solver = MpmSolver::new(Domain::unit_square(64), params, Some(1));
+    let jelly = solver.add_material(MaterialParams::default());
+    solver.add_particles(300, Vec2::new(0.5, 0.6), 0xffffffff, jelly);
+
+    let stable_dt = solver.stable_dt();
+    assert!(stable_dt < params.substep_dt());
+
+    let report = solver.update(0.0105);
+    assert_eq!(report.substeps(), 10);
+    assert!((report.simulated_time() - 0.01).abs() < 1e-6);
+
+    let per_substep = (params.substep_dt() / stable_dt).ceil() as u32;
+    assert_eq!(report.steps(), 10 * per_substep);
+    assert!(solver.last_dt() <= stable_dt);
+}
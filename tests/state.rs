@@ -0,0 +1,63 @@
+use webgl::mpm::{Domain, Emitter, MaterialParams, MpmSolver, SimParams, Vec2};
+
+fn positions(solver: &MpmSolver) -> Vec<(u32, u32)> {
+    solver
+        .particles()
+        .iter()
+        .map(|p| (p.position.x.to_bits(), p.position.y.to_bits()))
+        .collect()
+}
+
+#[test]
+fn loaded_state_resumes_identically() {
+    let mut original = MpmSolver::new(Domain::unit_square(32), SimParams::default(), Some(1));
+    let snow = original.add_material(MaterialParams::default());
+    let water = original.add_material(MaterialParams::water());
+    original.add_particles(200, Vec2::new(0.3, 0.5), 0xffffffff, snow);
+    original.add_particles(200, Vec2::new(0.7, 0.5), 0xffff8000, water);
+    for _ in 0..20 {
+        original.step(1e-4);
+    }
+
+    let mut restored = MpmSolver::load_state(&original.save_state()).unwrap();
+    assert_eq!(positions(&restored), positions(&original));
+
+    for _ in 0..20 {
+        original.step(1e-4);
+        restored.step(1e-4);
+    }
+    assert_eq!(positions(&restored), positions(&original));
+}
+
+#[test]
+fn foreign_data_is_rejected() {
+    assert!(MpmSolver::load_state(b"not a save").is_err());
+
+    let solver = MpmSolver::new(Domain::unit_square(8), SimParams::default(), None);
+    let mut bytes = solver.save_state();
+    bytes.truncate(bytes.len() - 1);
+    assert!(MpmSolver::load_state(&bytes).is_err());
+}
+
+#[test]
+fn unknown_emitter_material_is_rejected() {
+    let emitter = |material| Emitter::new(0.5, 0.5, 0.0, -1.0, 1.0, 100.0, material);
+
+    let mut solver = MpmSolver::new(Domain::unit_square(8), SimParams::default(), None);
+    let water = solver.add_material(MaterialParams::water());
+    solver.add_emitter(emitter(water));
+    let bytes = solver.save_state();
+    assert!(MpmSolver::load_state(&bytes).is_ok());
+
+    // The same save with the emitter pointing past the only material
+    let good = bincode::serialize(&emitter(water)).unwrap();
+    let bad = bincode::serialize(&emitter(water + 1)).unwrap();
+    let at = bytes
+        .windows(good.len())
+        .position(|w| w == good)
+        .expect("emitter is saved as is");
+    let mut corrupt = bytes.clone();
+    corrupt[at..at + good.len()].copy_from_slice(&bad);
+
+    assert!(MpmSolver::load_state(&corrupt).is_err());
+}